@@ -1,61 +1,212 @@
+use crate::err;
 use crate::errors::Errors;
+use crate::modifier::Hooks;
+use crate::pipeline::Group;
 use crate::pipeline::Pipeline;
 use crate::resource::Resource;
 use crate::resource::ResourceTypes;
+use crate::schema::Config;
+use crate::schema::FilePath;
+use crate::schema::Identifier;
+use crate::schema::Value;
+use crate::schema::Var;
+use crate::source::Source;
 use crate::step::InParallel;
 use crate::step::Step;
 use crate::task::Input;
 use crate::task::Output;
+use crate::task::Task;
 use crate::task::TaskDef;
 use crate::task::TaskResource;
+use crate::validate;
+use crate::validate::Diagnostic;
+use crate::validate::Problem;
 use serde_yaml;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+// Steps that only accept one step (e.g., try) wrap the adjusted steps in 'do'.
+fn sequential_step(mut steps: Vec<Step>) -> Step {
+    if steps.len() == 1 {
+        steps.pop().unwrap()
+    } else {
+        Step::Do(steps)
+    }
+}
+
+// Returns the resource as it is collected, i.e., renamed when a different
+// resource with the same name has been collected before, see ResourceDedup.
+pub(crate) fn collect_definition(
+    resource: &Resource,
+    resource_collector: &mut BTreeMap<String, Resource>,
+    options: &CookOptions,
+) -> Result<Resource, Errors> {
+    let yaml = |resource: &Resource| serde_yaml::to_string(resource).map_err(Errors::SerdeError);
+    let mut candidate = resource.clone();
+    let mut suffix = 1;
+    loop {
+        match resource_collector.get(&candidate.name) {
+            None => {
+                resource_collector.insert(candidate.name(), candidate.clone());
+                return Ok(candidate);
+            }
+            Some(collected) if yaml(collected)? == yaml(&candidate)? => return Ok(candidate),
+            Some(collected) => match options.resource_dedup {
+                ResourceDedup::Reject => {
+                    return Err(Errors::ConflictingResources {
+                        name: resource.name(),
+                        first: yaml(collected)?,
+                        second: yaml(resource)?,
+                    })
+                }
+                ResourceDedup::Rename => {
+                    suffix += 1;
+                    candidate = resource
+                        .clone()
+                        .with_name(format!("{}-{}", resource.name, suffix).as_str());
+                }
+            },
+        }
+    }
+}
+
+// Custom resource types are collected from the resources using them, along
+// with the custom types they are based on. Unlike resources, types are never
+// renamed, so all definitions must agree.
+pub(crate) fn collect_type_definition(
+    resource_type: &ResourceTypes,
+    type_collector: &mut BTreeMap<String, ResourceTypes>,
+) -> Result<(), Errors> {
+    if let ResourceTypes::Custom { ref type_, .. } = resource_type {
+        collect_type_definition(type_, type_collector)?;
+        let yaml = |resource_type: &ResourceTypes| {
+            serde_yaml::to_string(resource_type).map_err(Errors::SerdeError)
+        };
+        match type_collector.get(&resource_type.to_string()) {
+            None => {
+                type_collector.insert(resource_type.to_string(), resource_type.clone());
+            }
+            Some(collected) if yaml(collected)? == yaml(resource_type)? => { /* Do nothing. */ }
+            Some(collected) => {
+                return Err(Errors::ConflictingResourceTypes {
+                    name: resource_type.to_string(),
+                    first: yaml(collected)?,
+                    second: yaml(resource_type)?,
+                })
+            }
+        }
+    }
+    Ok(())
+}
 
 fn collect_resource_in_step(
     step: &Step,
     curr_resources: &mut BTreeMap<String, Resource>,
     resource_collector: &mut BTreeMap<String, Resource>,
+    options: &CookOptions,
 ) -> Result<Vec<Step>, Errors> {
     let mut adjusted_step = step.clone();
+    let mut prepended_steps = vec![];
     let mut parallel_to_get = vec![];
     match step {
         Step::Try(ref try_step) => {
-            collect_resource_in_step(try_step.try_.as_ref(), curr_resources, resource_collector)?;
+            adjusted_step = Step::try_(sequential_step(collect_resource_in_step(
+                try_step.try_.as_ref(),
+                curr_resources,
+                resource_collector,
+                options,
+            )?));
         }
         Step::Get(ref get_step) => {
-            if get_step.get.is_empty() {
-                curr_resources.insert(get_step.resource.name.clone(), get_step.resource.clone());
+            let name = if get_step.get.is_empty() {
+                get_step.resource.name.clone()
             } else {
-                curr_resources.insert(get_step.get.clone(), get_step.resource.clone());
-            }
-        }
-        Step::InParallel(ref in_parallel) => match in_parallel {
-            InParallel::Steps(ref steps) => {
-                let mut temp_curr_resources = BTreeMap::new();
-                let mut adjusted_parallel_steps = vec![];
-                for parallel_step in steps.iter() {
-                    adjusted_parallel_steps.append(&mut collect_resource_in_step(
-                        parallel_step,
-                        &mut temp_curr_resources,
-                        resource_collector,
-                    )?);
-                }
+                get_step.get.clone()
+            };
+            let resource = collect_definition(&get_step.resource, resource_collector, options)?;
+            if resource.name != get_step.resource.name {
+                // Keep the name of the fetched artifact for the following steps.
+                let mut renamed = get_step.clone();
+                renamed.get = name.clone();
+                renamed.resource = resource.clone();
+                adjusted_step = Step::Get(renamed);
+            }
+            curr_resources.insert(name, resource);
+        }
+        Step::InParallel(ref in_parallel) => {
+            let mut temp_curr_resources = BTreeMap::new();
+            let mut adjusted_parallel_steps = vec![];
+            for parallel_step in in_parallel.steps().iter() {
+                adjusted_parallel_steps.append(&mut collect_resource_in_step(
+                    parallel_step,
+                    &mut temp_curr_resources,
+                    resource_collector,
+                    options,
+                )?);
+            }
 
-                temp_curr_resources
-                    .iter()
-                    .map(|(k, v)| curr_resources.insert(k.clone(), v.clone()))
-                    .count();
+            temp_curr_resources
+                .iter()
+                .map(|(k, v)| curr_resources.insert(k.clone(), v.clone()))
+                .count();
 
-                adjusted_step = Step::InParallel(InParallel::Steps(adjusted_parallel_steps));
+            adjusted_step = Step::InParallel(in_parallel.with_steps(adjusted_parallel_steps));
+        }
+        Step::Do(ref steps) => {
+            // Steps in 'do' are running sequentially, resources fetched by
+            // previous steps are available to the following steps.
+            let mut adjusted_do_steps = vec![];
+            for do_step in steps.iter() {
+                adjusted_do_steps.append(&mut collect_resource_in_step(
+                    do_step,
+                    curr_resources,
+                    resource_collector,
+                    options,
+                )?);
             }
-            _ => {}
-        },
+
+            adjusted_step = Step::Do(adjusted_do_steps);
+        }
+        Step::Modified(ref modified) => {
+            let mut adjusted_steps = collect_resource_in_step(
+                modified.step(),
+                curr_resources,
+                resource_collector,
+                options,
+            )?;
+            let hooks = collect_resource_in_hooks(
+                &modified.modifiers().hooks,
+                curr_resources,
+                resource_collector,
+                options,
+            )?;
+            adjusted_step = Step::Modified(
+                modified
+                    .with_step(adjusted_steps.pop().unwrap())
+                    .with_hooks(hooks),
+            );
+            // Injected gets run once before the modified step rather than
+            // being modified together with it.
+            prepended_steps = adjusted_steps;
+        }
+        Step::SetPipeline(_) | Step::LoadVar(_) => { /* Do nothing. */ }
         Step::Put(ref put_step) => {
-            if put_step.put.is_empty() {
-                resource_collector
-                    .insert(put_step.resource.name.clone(), put_step.resource.clone());
+            let name = if put_step.put.is_empty() {
+                put_step.resource.name.clone()
             } else {
-                resource_collector.insert(put_step.put.clone(), put_step.resource.clone());
+                put_step.put.clone()
+            };
+            let resource = collect_definition(&put_step.resource, resource_collector, options)?;
+            if resource.name != put_step.resource.name {
+                let mut renamed = put_step.clone();
+                renamed.put = name.clone();
+                renamed.resource = resource.clone();
+                adjusted_step = Step::Put(renamed);
+            }
+            // Every put is followed by an implicit get of the new version, so
+            // the following steps don't need to get the resource again.
+            if !put_step.no_get {
+                curr_resources.insert(name, resource);
             }
         }
         Step::Task(ref task_step) => {
@@ -70,16 +221,23 @@ fn collect_resource_in_step(
                         ..
                     } = inp
                     {
-                        if !curr_resources.contains_key(resource.name().as_str()) {
-                            curr_resources.insert(resource.name(), resource.clone());
-                            match get_as {
-                                Some(ref get_as_name) => parallel_to_get
-                                    .push(resource.as_get_resource().get_as(get_as_name.as_str())),
-                                None => parallel_to_get.push(resource.as_get_resource().get()),
+                        let name = get_as.clone().unwrap_or_else(|| resource.name());
+                        let collected = collect_definition(resource, resource_collector, options)?;
+                        // The artifact may have been fetched from a different
+                        // resource that was collected under the same name.
+                        let fetched = curr_resources
+                            .get(name.as_str())
+                            .map(|fetched| fetched.name == collected.name);
+                        if fetched != Some(true) {
+                            if get_as.is_some() || collected.name != resource.name {
+                                parallel_to_get
+                                    .push(collected.as_get_resource().get_as(name.as_str()));
+                            } else {
+                                parallel_to_get.push(collected.as_get_resource().get());
                             }
-
-                            inputs_for_new_config.push(Input::new(resource.name().as_str()));
+                            curr_resources.insert(name.clone(), collected);
                         }
+                        inputs_for_new_config.push(Input::new(name.as_str()));
                     } else if let TaskResource::Output { ref name, .. } = inp {
                         inputs_for_new_config.push(Input::new(name.as_str()));
                     }
@@ -100,12 +258,20 @@ fn collect_resource_in_step(
                 TaskDef::Config { .. } => {
                     adjusted_step =
                         Step::Task(task_step.clone().mutate_task_config(|task_config| {
+                            // Inputs and outputs configured by the user keep their
+                            // options, e.g., path or optional.
                             let mut new_task_config = task_config.clone();
-                            if !inputs_for_new_config.is_empty() {
-                                new_task_config.inputs = Some(inputs_for_new_config.clone());
+                            for input in inputs_for_new_config.iter() {
+                                let inputs = new_task_config.inputs.get_or_insert_with(Vec::new);
+                                if !inputs.iter().any(|i| i.name == input.name) {
+                                    inputs.push(input.clone());
+                                }
                             }
-                            if !outputs_for_new_config.is_empty() {
-                                new_task_config.outputs = Some(outputs_for_new_config.clone());
+                            for output in outputs_for_new_config.iter() {
+                                let outputs = new_task_config.outputs.get_or_insert_with(Vec::new);
+                                if !outputs.iter().any(|o| o.name == output.name) {
+                                    outputs.push(output.clone());
+                                }
                             }
                             new_task_config
                         }));
@@ -113,52 +279,64 @@ fn collect_resource_in_step(
             }
 
             // 2. Check if we need to get resource for task.image.
+            let mut image_resource = None;
             if let Some(image) = task_step.image.as_ref() {
-                if !curr_resources.contains_key(image.resource.name.as_str()) {
-                    curr_resources.insert(image.resource.name(), image.resource.clone());
-                    parallel_to_get.push(
-                        task_step
-                            .image
-                            .as_ref()
-                            .unwrap()
-                            .resource
-                            .as_get_resource()
-                            .get(),
-                    );
+                let collected = collect_definition(&image.resource, resource_collector, options)?;
+                if !curr_resources.contains_key(collected.name.as_str()) {
+                    curr_resources.insert(collected.name(), collected.clone());
+                    parallel_to_get.push(collected.as_get_resource().get());
                 }
+                image_resource = Some(collected);
             }
 
             // 3. Check task hooks.
-            if let Some(ref on_failure) = task_step.on_failure.as_ref() {
-                collect_resource_in_step(on_failure.as_ref(), curr_resources, resource_collector)?;
-            }
-            if let Some(ref on_abort) = task_step.on_abort.as_ref() {
-                collect_resource_in_step(on_abort.as_ref(), curr_resources, resource_collector)?;
-            }
-            if let Some(ref on_success) = task_step.on_failure.as_ref() {
-                collect_resource_in_step(on_success.as_ref(), curr_resources, resource_collector)?;
+            let hooks = collect_resource_in_hooks(
+                &task_step.hooks,
+                curr_resources,
+                resource_collector,
+                options,
+            )?;
+            if let Step::Task(ref mut adjusted_task) = adjusted_step {
+                adjusted_task.hooks = hooks;
+                if let (Some(image), Some(resource)) =
+                    (adjusted_task.image.as_mut(), image_resource)
+                {
+                    image.resource = resource;
+                }
             }
         }
     }
 
-    curr_resources
-        .iter()
-        .map(|(k, v)| resource_collector.insert(k.clone(), v.clone()))
-        .count();
-
     if !parallel_to_get.is_empty() {
-        Ok(vec![
-            Step::InParallel(InParallel::Steps(parallel_to_get)),
-            adjusted_step,
-        ])
-    } else {
-        Ok(vec![adjusted_step])
+        prepended_steps.push(Step::InParallel(InParallel::Steps(parallel_to_get)));
     }
+    prepended_steps.push(adjusted_step);
+    Ok(prepended_steps)
+}
+
+// Hooks are running after the hooked step, so they can use resources fetched
+// before, but resources fetched by hooks are not available to following steps.
+fn collect_resource_in_hooks(
+    hooks: &Hooks,
+    curr_resources: &BTreeMap<String, Resource>,
+    resource_collector: &mut BTreeMap<String, Resource>,
+    options: &CookOptions,
+) -> Result<Hooks, Errors> {
+    hooks.try_map(|hook| {
+        let mut hook_resources = curr_resources.clone();
+        Ok(sequential_step(collect_resource_in_step(
+            hook,
+            &mut hook_resources,
+            resource_collector,
+            options,
+        )?))
+    })
 }
 
 fn collect_resource(
     pipeline: &Pipeline,
     resource_collector: &mut BTreeMap<String, Resource>,
+    options: &CookOptions,
 ) -> Result<Pipeline, Errors> {
     let mut adjusted_pipeline = pipeline.clone();
     // Reset the plan, since we will reconstruct it.
@@ -173,9 +351,11 @@ fn collect_resource(
         let mut curr_resources = BTreeMap::new();
         for step in job.plan.iter() {
             let mut adjusted_steps =
-                collect_resource_in_step(step, &mut curr_resources, resource_collector)?;
+                collect_resource_in_step(step, &mut curr_resources, resource_collector, options)?;
             curr_job.plan.append(&mut adjusted_steps);
         }
+        curr_job.hooks =
+            collect_resource_in_hooks(&job.hooks, &curr_resources, resource_collector, options)?;
 
         // Append the adjusted job to the pipeline.
         adjusted_pipeline.jobs.push(curr_job);
@@ -184,32 +364,370 @@ fn collect_resource(
     Ok(adjusted_pipeline)
 }
 
-fn optimize_pipeline(pipeline: &Pipeline) -> Result<Pipeline, Errors> {
+pub(crate) fn optimize_pipeline(
+    pipeline: &Pipeline,
+    options: &CookOptions,
+) -> Result<Pipeline, Errors> {
+    // Declared resources and types are kept even if no step uses them.
     let mut resource_collector = BTreeMap::new();
-    let pipeline = collect_resource(pipeline, &mut resource_collector)?;
+    for resource in pipeline.resources.iter() {
+        collect_definition(resource, &mut resource_collector, options)?;
+    }
+    let mut type_collector = BTreeMap::new();
+    for resource_type in pipeline.resource_types.iter() {
+        collect_type_definition(resource_type, &mut type_collector)?;
+    }
+
+    let pipeline = collect_resource(pipeline, &mut resource_collector, options)?;
+    for resource in resource_collector.values() {
+        collect_type_definition(&resource.type_, &mut type_collector)?;
+    }
     Ok(pipeline
-        .with_resources(resource_collector.iter().map(|(_, v)| v.clone()).collect())
-        .with_resource_types(
-            resource_collector
+        .with_resources(resource_collector.into_values().collect())
+        .with_resource_types(type_collector.into_values().collect()))
+}
+
+// A group that is appended to pipelines with groups, so that jobs are still
+// reachable from the UI.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum CatchAllGroup {
+    #[default]
+    None,
+    // Prepends an "all" group containing every job.
+    All,
+    // Appends an "ungrouped" group containing jobs that are not in any group.
+    Ungrouped,
+}
+
+// How the defaults of custom resource types are applied to the resources of
+// that type.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum ResourceTypeDefaults {
+    // Copies defaults into the source of each resource, unless the resource
+    // sets the key itself.
+    #[default]
+    Merge,
+    // Drops keys from the source of each resource whose value equals the
+    // default, leaving it to Concourse to apply them.
+    Drop,
+}
+
+// How tasks without a name are named. Both schemes give the same names every
+// time the pipeline is cooked.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum TaskNaming {
+    // '<job>-task-<n>', where n counts the tasks of the job in plan order.
+    #[default]
+    Position,
+    // 'task-<hash>', where the hash is derived from the content of the task,
+    // so names don't change when the plan is reordered.
+    ContentHash,
+}
+
+// What happens when different resources share a name, e.g., two git
+// resources for repos of the same name.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub enum ResourceDedup {
+    // Fails with both definitions.
+    #[default]
+    Reject,
+    // Keeps the first resource and renames the others to '<name>-<n>'. Steps
+    // still refer to the fetched artifacts by the original name.
+    Rename,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CookOptions {
+    pub(crate) catch_all_group: CatchAllGroup,
+    pub(crate) resource_type_defaults: ResourceTypeDefaults,
+    pub(crate) task_naming: TaskNaming,
+    pub(crate) resource_dedup: ResourceDedup,
+}
+
+impl CookOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_catch_all_group(mut self, catch_all_group: CatchAllGroup) -> Self {
+        self.catch_all_group = catch_all_group;
+        self
+    }
+
+    pub fn with_resource_type_defaults(
+        mut self,
+        resource_type_defaults: ResourceTypeDefaults,
+    ) -> Self {
+        self.resource_type_defaults = resource_type_defaults;
+        self
+    }
+
+    pub fn with_task_naming(mut self, task_naming: TaskNaming) -> Self {
+        self.task_naming = task_naming;
+        self
+    }
+
+    pub fn with_resource_dedup(mut self, resource_dedup: ResourceDedup) -> Self {
+        self.resource_dedup = resource_dedup;
+        self
+    }
+}
+
+fn map_tasks<F: FnMut(&Task) -> Result<Task, Errors>>(
+    step: &Step,
+    mapper: &mut F,
+) -> Result<Step, Errors> {
+    Ok(match step {
+        Step::Get(_) | Step::Put(_) | Step::SetPipeline(_) | Step::LoadVar(_) => step.clone(),
+        Step::Task(ref task) => {
+            let mut task = mapper(task)?;
+            task.hooks = task.hooks.try_map(|hook| map_tasks(hook, mapper))?;
+            Step::Task(task)
+        }
+        Step::InParallel(ref in_parallel) => Step::InParallel(
+            in_parallel.with_steps(
+                in_parallel
+                    .steps()
+                    .iter()
+                    .map(|step| map_tasks(step, mapper))
+                    .collect::<Result<Vec<Step>, Errors>>()?,
+            ),
+        ),
+        Step::Try(ref try_step) => Step::try_(map_tasks(try_step.try_.as_ref(), mapper)?),
+        Step::Do(ref steps) => Step::Do(
+            steps
                 .iter()
-                .filter_map(|(_, v)| {
-                    if let ResourceTypes::Custom { .. } = v.type_ {
-                        Some((v.type_.to_string(), v.type_.clone()))
-                    } else {
-                        None
-                    }
-                })
-                .collect::<BTreeMap<String, ResourceTypes>>()
-                .into_iter()
-                .map(|(_, v)| v.clone())
-                .collect(),
-        ))
+                .map(|step| map_tasks(step, mapper))
+                .collect::<Result<Vec<Step>, Errors>>()?,
+        ),
+        Step::Modified(ref modified) => {
+            let step = map_tasks(modified.step(), mapper)?;
+            let hooks = modified
+                .modifiers()
+                .hooks
+                .try_map(|hook| map_tasks(hook, mapper))?;
+            Step::Modified(modified.with_step(step).with_hooks(hooks))
+        }
+    })
+}
+
+// FNV-1a, unlike std's DefaultHasher it is stable across Rust releases.
+fn stable_hash(content: &str) -> u64 {
+    content.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+// Generated names that collide with other tasks are reported to 'diagnostics'.
+pub(crate) fn name_tasks(
+    pipeline: &Pipeline,
+    options: &CookOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<Pipeline, Errors> {
+    let mut named_pipeline = pipeline.clone();
+    for job in named_pipeline.jobs.iter_mut() {
+        let job_name = job.name();
+        let mut position = 0;
+        // Names of the tasks in the job, and whether they were generated.
+        let mut names: Vec<(Identifier, bool)> = vec![];
+        let mut mapper = |task: &Task| -> Result<Task, Errors> {
+            position += 1;
+            if let Some(name) = task.name() {
+                names.push((name, false));
+                return Ok(task.clone());
+            }
+
+            let name = match options.task_naming {
+                TaskNaming::Position => format!("{}-task-{}", job_name, position),
+                TaskNaming::ContentHash => {
+                    // Hooks are named on their own, leave them out of the hash.
+                    let content = task.clone().without_hooks().with_name("");
+                    let yaml = serde_yaml::to_string(&content).map_err(Errors::SerdeError)?;
+                    format!("task-{:016x}", stable_hash(yaml.as_str()))
+                }
+            };
+            names.push((name.clone(), true));
+            Ok(task.clone().with_name(name.as_str()))
+        };
+
+        job.plan = job
+            .plan
+            .iter()
+            .map(|step| map_tasks(step, &mut mapper))
+            .collect::<Result<Vec<Step>, Errors>>()?;
+        job.hooks = job.hooks.try_map(|hook| map_tasks(hook, &mut mapper))?;
+
+        // Tasks that are named the same on purpose are fine, but a generated
+        // name must not shadow any other task.
+        let mut reported = BTreeSet::new();
+        for (name, generated) in names.iter() {
+            if *generated
+                && names.iter().filter(|(other, _)| other == name).count() > 1
+                && reported.insert(name)
+            {
+                diagnostics.push(Diagnostic::new(
+                    format!("jobs[{}]", job_name).as_str(),
+                    Problem::GeneratedTaskNameCollision { task: name.clone() },
+                ));
+            }
+        }
+    }
+    Ok(named_pipeline)
+}
+
+// Defaults apply to the source as it is serialized, i.e., the typed source
+// merged with the raw one, so typed fields are neither overridden nor missed.
+fn apply_resource_type_defaults(
+    pipeline: &mut Pipeline,
+    options: &CookOptions,
+) -> Result<(), Errors> {
+    for resource in pipeline.resources.iter_mut() {
+        let defaults = match resource.type_.defaults() {
+            Some(defaults) if !defaults.is_empty() => defaults,
+            _ => continue,
+        };
+        let serialized = serde_yaml::to_value(&resource.source).map_err(Errors::SerdeError)?;
+        let mut source = match Value::from_yaml(serialized) {
+            Some(Value::Map(source)) => source,
+            _ => Config::new(),
+        };
+        match options.resource_type_defaults {
+            ResourceTypeDefaults::Merge => {
+                for (key, value) in defaults.iter() {
+                    source.entry(key.clone()).or_insert_with(|| value.clone());
+                }
+            }
+            ResourceTypeDefaults::Drop => {
+                source.retain(|key, value| defaults.get(key) != Some(value));
+            }
+        }
+        resource.source = Source::raw(source);
+    }
+    Ok(())
+}
+
+// Groups named like the catch-all group are reported by validate.
+fn append_catch_all_group(pipeline: &mut Pipeline, options: &CookOptions) {
+    if pipeline.groups.is_empty() {
+        return;
+    }
+
+    let (name, jobs) = match options.catch_all_group {
+        CatchAllGroup::None => return,
+        CatchAllGroup::All => ("all", pipeline.jobs.iter().map(|job| job.name()).collect()),
+        CatchAllGroup::Ungrouped => {
+            let grouped = pipeline
+                .groups
+                .iter()
+                .flat_map(|group| group.jobs.iter())
+                .collect::<BTreeSet<_>>();
+            (
+                "ungrouped",
+                pipeline
+                    .jobs
+                    .iter()
+                    .map(|job| job.name())
+                    .filter(|job| !grouped.contains(job))
+                    .collect::<Vec<_>>(),
+            )
+        }
+    };
+
+    if jobs.is_empty() {
+        return;
+    }
+
+    let group = Group {
+        name: name.to_string(),
+        jobs,
+    };
+    match options.catch_all_group {
+        CatchAllGroup::All => pipeline.groups.insert(0, group),
+        _ => pipeline.groups.push(group),
+    }
+}
+
+fn cook_pipeline_file(
+    pipeline: &Pipeline,
+    file: &str,
+    options: &CookOptions,
+    files: &mut BTreeMap<FilePath, String>,
+) -> Result<(), Errors> {
+    let yaml = cook_pipeline_with_options(pipeline, options)?;
+    match files.get(file) {
+        Some(existing) if *existing != yaml => {
+            return err!(
+                "Different pipelines are cooked into the same file '{}'",
+                file
+            );
+        }
+        Some(_) => return Ok(()),
+        None => {
+            files.insert(file.to_string(), yaml);
+        }
+    }
+
+    let mut nested_pipelines = vec![];
+    for job in pipeline.jobs.iter() {
+        for step in job.steps().into_iter().flat_map(|step| step.walk()) {
+            if let Step::SetPipeline(ref set_pipeline) = step {
+                if let Some(ref nested_pipeline) = set_pipeline.pipeline {
+                    nested_pipelines.push((&set_pipeline.file, nested_pipeline.as_ref()));
+                }
+            }
+        }
+    }
+
+    for (nested_file, nested_pipeline) in nested_pipelines {
+        cook_pipeline_file(nested_pipeline, nested_file, options, files)?;
+    }
+
+    Ok(())
 }
 
 pub fn cook_pipeline(pipeline: &Pipeline) -> Result<String, Errors> {
-    let pipeline = optimize_pipeline(pipeline)?;
-    match serde_yaml::to_string(&pipeline) {
+    cook_pipeline_with_options(pipeline, &CookOptions::default())
+}
+
+pub fn cook_pipeline_with_options(
+    pipeline: &Pipeline,
+    options: &CookOptions,
+) -> Result<String, Errors> {
+    let (pipeline, diagnostics) = validate::diagnose(pipeline, options)?;
+    if !diagnostics.is_empty() {
+        return Err(Errors::ValidationErrors(diagnostics));
+    }
+    let mut optimized = optimize_pipeline(&pipeline, options)?;
+    apply_resource_type_defaults(&mut optimized, options)?;
+    append_catch_all_group(&mut optimized, options);
+    match serde_yaml::to_string(&optimized) {
         Ok(yaml) => Ok(yaml),
         Err(e) => Err(Errors::SerdeError(e)),
     }
 }
+
+// Returns names of vars that must be provided when setting the pipeline, e.g.,
+// by 'fly set-pipeline --var'. Local vars and vars from var sources are excluded.
+pub fn required_vars(pipeline: &Pipeline) -> Result<BTreeSet<Identifier>, Errors> {
+    let options = CookOptions::default();
+    let pipeline = name_tasks(pipeline, &options, &mut vec![])?;
+    let pipeline = optimize_pipeline(&pipeline, &options)?;
+    Ok(Var::find_all_in(&pipeline)
+        .map_err(Errors::SerdeError)?
+        .into_iter()
+        .filter(|var| var.source.is_none())
+        .map(|var| var.path)
+        .collect())
+}
+
+// Cooks the pipeline into 'file' and every pipeline attached to its
+// set_pipeline steps into their own files. Returns file paths mapped to YAML.
+pub fn cook_pipeline_files(
+    pipeline: &Pipeline,
+    file: &str,
+    options: &CookOptions,
+) -> Result<BTreeMap<FilePath, String>, Errors> {
+    let mut files = BTreeMap::new();
+    cook_pipeline_file(pipeline, file, options, &mut files)?;
+    Ok(files)
+}
@@ -1,12 +1,41 @@
+use crate::validate::Diagnostic;
 use serde_yaml;
 use thiserror::Error;
 
+fn lines(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[derive(Debug, Error)]
 pub enum Errors {
     #[error("Cannot cook concourse configuration:\n{0}")]
     CookError(String),
     #[error("Cannot cook concourse configuration due to serde error:\n{0}")]
     SerdeError(serde_yaml::Error),
+    #[error(
+        "Cannot cook concourse configuration:\nResource '{name}' has conflicting definitions:\n{first}---\n{second}"
+    )]
+    ConflictingResources {
+        name: String,
+        first: String,
+        second: String,
+    },
+    #[error(
+        "Cannot cook concourse configuration:\nResource type '{name}' has conflicting definitions:\n{first}---\n{second}"
+    )]
+    ConflictingResourceTypes {
+        name: String,
+        first: String,
+        second: String,
+    },
+    #[error("Cannot cook concourse configuration:\n{}", lines(.0))]
+    ValidationErrors(Vec<Diagnostic>),
+    #[error("Cannot parse concourse configuration:\n{}", .0.join("\n"))]
+    ParseErrors(Vec<String>),
 }
 
 impl Errors {
@@ -1,18 +1,56 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod examples {
     use crate::{
-        cook::cook_pipeline,
-        job::Job,
+        cook::{
+            cook_pipeline, cook_pipeline_files, cook_pipeline_with_options, required_vars,
+            CatchAllGroup, CookOptions, ResourceDedup, ResourceTypeDefaults, TaskNaming,
+        },
+        graph::Graph,
+        job::{BuildLogRetention, Job},
+        load_var::{LoadVar, LoadVarFormat},
+        modifier::{AcrossVar, MaxInFlight},
+        parse::parse_pipeline,
         pipeline::Pipeline,
-        resource::{Resource, ResourceTypes},
+        put::PutInputs,
+        resource::{
+            community::{
+                semver_type, GithubReleaseParams, GithubReleaseSource, MockParams, MockSource,
+                PoolAction, PoolSource, S3Params, S3Source, SemverBump, SemverParams, SemverSource,
+                SlackNotificationParams, SlackNotificationSource,
+            },
+            CheckEvery, Resource, ResourceTypes,
+        },
+        schema::{ByteSize, Duration, Value, Var, Version},
+        set_pipeline::SetPipeline,
+        source::{
+            Day, DockerImageSource, GitGetParams, GitSource, GitVersion, ImageFormat,
+            RegistryImageGetParams, RegistryImageSource, RegistryImageVersion, Submodules,
+            TimeSource, TypedSource,
+        },
         step::Step,
-        task::{Command, Task, TaskResource},
+        task::{Command, ContainerLimits, Input, Output, Task, TaskResource},
+        validate::validate,
+        var_source::{AwsConfig, VarSource, VaultConfig},
     };
 
     fn mock_resource_type() -> ResourceTypes {
         ResourceTypes::new("mock", ResourceTypes::RegistryImage)
     }
 
+    // Cooked pipelines parse back into pipelines cooking to the same YAML,
+    // up to the order of keys in typed sources.
+    fn cook_round_trip(pipeline: &Pipeline) -> String {
+        let yaml = cook_pipeline(pipeline).unwrap();
+        let parsed = parse_pipeline(yaml.as_str()).unwrap();
+        let value = |yaml: &str| serde_yaml::from_str::<serde_yaml::Value>(yaml).unwrap();
+        assert_eq!(
+            value(cook_pipeline(&parsed).unwrap().as_str()),
+            value(yaml.as_str())
+        );
+        yaml
+    }
+
     // https://concourse-ci.org/hello-world-example.html
     #[test]
     fn hello_world_example() {
@@ -20,13 +58,13 @@ mod examples {
             Job::new("job").with_public(true).then(
                 Task::new()
                     .with_name("simple-task")
-                    .run(&Command::new("echo", &vec!["Hello world!"]))
+                    .run(&Command::new("echo", &["Hello world!"]))
                     .to_step(),
             ),
         );
 
         assert_eq!(
-            cook_pipeline(&pipeline).unwrap().as_str(),
+            cook_round_trip(&pipeline).as_str(),
             r#"jobs:
 - name: job
   public: true
@@ -54,14 +92,14 @@ mod examples {
                 Task::new()
                     .with_name("simple-task")
                     .mutate_task_config(|task_config| {
-                        task_config.run(&Command::new("echo", &vec!["Hello world!"]))
+                        task_config.run(&Command::new("echo", &["Hello world!"]))
                     })
                     .to_step(),
             ),
         );
 
         assert_eq!(
-            cook_pipeline(&pipeline).unwrap().as_str(),
+            cook_round_trip(&pipeline).as_str(),
             r#"jobs:
 - name: job
   public: true
@@ -83,30 +121,38 @@ mod examples {
     }
 
     // https://concourse-ci.org/pipeline-vars-example.html
-    // Variables should be load by the fly command.
-    // We don't process it in our library.
+    // Variables should be load by the fly command, required_vars() tells
+    // which ones are needed.
     #[test]
     fn pipeline_vars_example() {
+        let first = Var::new("first");
+        let number = Var::new("number");
+        let hello = Var::new("hello");
+        let greeting = format!("Hello, {}!", hello);
         let pipeline = Pipeline::new()
             .append(
-                Job::new("((first))-job").with_public(true).then(
-                    Task::new()
-                        .with_name("simple-task")
-                        .run(&Command::new("echo", &vec!["Hello, ((hello))!"]))
-                        .to_step(),
-                ),
+                Job::new(format!("{}-job", first).as_str())
+                    .with_public(true)
+                    .then(
+                        Task::new()
+                            .with_name("simple-task")
+                            .run(&Command::new("echo", &[greeting.as_str()]))
+                            .to_step(),
+                    ),
             )
             .append(
-                Job::new("level-((number))-job").with_public(true).then(
-                    Task::new()
-                        .with_name("simple-task")
-                        .run(&Command::new("echo", &vec!["Hello, ((hello))!"]))
-                        .to_step(),
-                ),
+                Job::new(format!("level-{}-job", number).as_str())
+                    .with_public(true)
+                    .then(
+                        Task::new()
+                            .with_name("simple-task")
+                            .run(&Command::new("echo", &[greeting.as_str()]))
+                            .to_step(),
+                    ),
             );
 
         assert_eq!(
-            cook_pipeline(&pipeline).unwrap().as_str(),
+            cook_round_trip(&pipeline).as_str(),
             r#"jobs:
 - name: ((first))-job
   public: true
@@ -138,6 +184,79 @@ mod examples {
         - Hello, ((hello))!
 "#
         );
+        assert_eq!(
+            required_vars(&pipeline)
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec!["first", "hello", "number"]
+        );
+    }
+
+    // https://concourse-ci.org/vars.html
+    #[test]
+    fn typed_vars() {
+        let registry_password = Var::new("registry").field("password");
+        let app_version = Var::new("app_version");
+        let token = Var::new("token");
+        let image = Resource::registry_image("example/app")
+            .with_source(&[("username", "ci"), ("password", &registry_password)]);
+
+        let pipeline = Pipeline::new().append(
+            Job::new("job")
+                .then(
+                    image
+                        .as_get_resource()
+                        .with_version(Version::custom(&[("tag", &app_version)]))
+                        .get(),
+                )
+                .then(
+                    Task::new()
+                        .with_name("smoke-test")
+                        .with_params(&[("TOKEN", &token)])
+                        .run(&Command::new("./smoke-test.sh", &[&app_version]))
+                        .to_step(),
+                ),
+        );
+
+        assert_eq!(
+            cook_round_trip(&pipeline),
+            r#"jobs:
+- name: job
+  plan:
+  - get: example/app
+    version:
+      tag: ((app_version))
+  - task: smoke-test
+    config:
+      platform: linux
+      image_resource:
+        type: registry-image
+        source:
+          repository: busybox
+      run:
+        path: ./smoke-test.sh
+        args:
+        - ((app_version))
+    params:
+      TOKEN: ((token))
+resources:
+- name: example/app
+  type: registry-image
+  icon: docker
+  source:
+    password: ((registry.password))
+    repository: example/app
+    username: ci
+"#
+        );
+        assert_eq!(
+            required_vars(&pipeline)
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec!["app_version", "registry", "token"]
+        );
     }
 
     // https://concourse-ci.org/time-trigger-example.html
@@ -151,13 +270,13 @@ mod examples {
                 .then(
                     Task::new()
                         .with_name("simple-task")
-                        .run(&Command::new("echo", &vec!["Hello, world!"]))
+                        .run(&Command::new("echo", &["Hello, world!"]))
                         .to_step(),
                 ),
         );
 
         assert_eq!(
-            cook_pipeline(&pipeline).unwrap().as_str(),
+            cook_round_trip(&pipeline).as_str(),
             r#"jobs:
 - name: job
   public: true
@@ -195,14 +314,14 @@ resources:
             Job::new("job").then(
                 Task::new()
                     .with_name("list-files")
-                    .with_inputs(&vec![&concourse_docs_git.as_task_input_resource()])
-                    .run(&Command::new("ls", &vec!["./concourse-docs-git"]))
+                    .with_inputs(&[&concourse_docs_git.as_task_input_resource()])
+                    .run(&Command::new("ls", &["./concourse-docs-git"]))
                     .to_step(),
             ),
         );
 
         assert_eq!(
-            cook_pipeline(&pipeline).unwrap(),
+            cook_round_trip(&pipeline),
             r#"jobs:
 - name: job
   plan:
@@ -236,10 +355,7 @@ resources:
     #[test]
     fn hooks_example() {
         let echo = |what: &str, state: &str| {
-            Command::new(
-                "echo",
-                &vec![format!("This {} was {}!", what, state).as_str()],
-            )
+            Command::new("echo", &[format!("This {} was {}!", what, state).as_str()])
         };
 
         let pipeline = Pipeline::new().append(
@@ -266,7 +382,7 @@ resources:
                 .then(
                     Task::new()
                         .with_name("successful-task")
-                        .run(&Command::new("sh", &vec!["-lc", "exit 0"]))
+                        .run(&Command::new("sh", &["-lc", "exit 0"]))
                         .on_success(
                             Task::new()
                                 .with_name("task-success")
@@ -284,7 +400,7 @@ resources:
                 .then(
                     Task::new()
                         .with_name("failing-task")
-                        .run(&Command::new("sh", &vec!["-lc", "exit 1"]))
+                        .run(&Command::new("sh", &["-lc", "exit 1"]))
                         .on_failure(
                             Task::new()
                                 .with_name("task-failure")
@@ -296,7 +412,7 @@ resources:
         );
 
         assert_eq!(
-            cook_pipeline(&pipeline).unwrap(),
+            cook_round_trip(&pipeline),
             r#"jobs:
 - name: job
   public: true
@@ -416,7 +532,7 @@ resources:
         );
 
         assert_eq!(
-            cook_pipeline(&pipeline).unwrap(),
+            cook_round_trip(&pipeline),
             r#"jobs:
 - name: fetch-and-run-image
   plan:
@@ -441,6 +557,57 @@ resources:
         );
     }
 
+    // https://concourse-ci.org/task-step.html
+    // https://github.com/concourse/docker-image-resource#source-configuration
+    #[test]
+    fn fetching_and_using_a_docker_image() {
+        let golang_image = DockerImageSource::new("golang")
+            .with_tag("1.17")
+            .with_credentials("ci", &Var::new("docker_hub").field("password"))
+            .with_insecure_registries(&["registry.internal:5000"])
+            .with_registry_mirror("https://mirror.internal")
+            .to_resource();
+        let pipeline = Pipeline::new().append(
+            Job::new("fetch-and-run-image").then(
+                Task::new()
+                    .with_name("use-fetched-image-in-task")
+                    .with_image(golang_image.as_task_image_resource())
+                    .run(&Command::new("go", &["version"]))
+                    .to_step(),
+            ),
+        );
+
+        assert_eq!(
+            cook_round_trip(&pipeline),
+            r#"jobs:
+- name: fetch-and-run-image
+  plan:
+  - in_parallel:
+    - get: golang
+  - task: use-fetched-image-in-task
+    config:
+      platform: linux
+      run:
+        path: go
+        args:
+        - version
+    image: golang
+resources:
+- name: golang
+  type: docker-image
+  icon: docker
+  source:
+    insecure_registries:
+    - registry.internal:5000
+    password: ((docker_hub.password))
+    registry_mirror: https://mirror.internal
+    repository: golang
+    tag: '1.17'
+    username: ci
+"#
+        );
+    }
+
     // https://concourse-ci.org/task-inputs-outputs-example.html
     #[test]
     fn task_inputs_outputs_example() {
@@ -470,7 +637,7 @@ resources:
         );
 
         assert_eq!(
-            cook_pipeline(&pipeline).unwrap(),
+            cook_round_trip(&pipeline),
             r#"jobs:
 - name: create-and-consume
   plan:
@@ -526,7 +693,7 @@ resources:
         );
 
         assert_eq!(
-            cook_pipeline(&pipeline).unwrap(),
+            cook_round_trip(&pipeline),
             r#"jobs:
 - name: task-input-mapping
   plan:
@@ -581,7 +748,7 @@ resource_types:
         );
 
         assert_eq!(
-            cook_pipeline(&pipeline).unwrap(),
+            cook_round_trip(&pipeline),
             r#"jobs:
 - name: task_output_mapping
   plan:
@@ -632,7 +799,7 @@ resource_types:
         );
 
         assert_eq!(
-            cook_pipeline(&pipeline).unwrap(),
+            cook_round_trip(&pipeline),
             r#"jobs:
 - name: get-and-pull
   plan:
@@ -666,7 +833,7 @@ resource_types:
         );
 
         assert_eq!(
-            cook_pipeline(&pipeline).unwrap(),
+            cook_round_trip(&pipeline),
             r#"jobs:
 - name: dummy-job
   plan:
@@ -684,7 +851,2115 @@ resource_types:
     on_success:
       try:
         put: test-logs
+resources:
+- name: test-logs
+  type: mock
+resource_types:
+- name: mock
+  type: registry-image
+"#
+        );
+    }
+
+    // https://concourse-ci.org/in-parallel-step.html
+    #[test]
+    fn in_parallel_with_limit_and_fail_fast() {
+        let repo = Resource::new("repo", &mock_resource_type());
+        let pipeline = Pipeline::new().append(
+            Job::new("parallel-tests").parallel_with(
+                Some(2),
+                true,
+                &[
+                    Task::new()
+                        .with_name("unit")
+                        .with_inputs(&[&repo.as_task_input_resource()])
+                        .run(&Command::new("make", &["unit"]))
+                        .to_step(),
+                    Task::new()
+                        .with_name("lint")
+                        .run(&Command::new("make", &["lint"]))
+                        .to_step(),
+                ],
+            ),
+        );
+
+        assert_eq!(
+            cook_pipeline(&pipeline).unwrap(),
+            r#"jobs:
+- name: parallel-tests
+  plan:
+  - in_parallel:
+      steps:
+      - in_parallel:
+        - get: repo
+      - task: unit
+        config:
+          platform: linux
+          image_resource:
+            type: registry-image
+            source:
+              repository: busybox
+          run:
+            path: make
+            args:
+            - unit
+          inputs:
+          - name: repo
+      - task: lint
+        config:
+          platform: linux
+          image_resource:
+            type: registry-image
+            source:
+              repository: busybox
+          run:
+            path: make
+            args:
+            - lint
+      limit: 2
+      fail_fast: true
+resources:
+- name: repo
+  type: mock
+resource_types:
+- name: mock
+  type: registry-image
+"#
+        );
+    }
+
+    // https://concourse-ci.org/set-pipeline-step.html
+    #[test]
+    fn set_pipeline_step() {
+        let child = Pipeline::new().append(
+            Job::new("child-job").then(
+                Task::new()
+                    .with_name("hello")
+                    .run(&Command::new("echo", &["hello from child"]))
+                    .to_step(),
+            ),
+        );
+
+        let pipeline = Pipeline::new().append(
+            Job::new("set-pipelines")
+                .then(SetPipeline::self_("ci/pipelines/parent.yml").to_step())
+                .then(
+                    SetPipeline::new("child", "ci/pipelines/child.yml")
+                        .with_instance_vars(&[("branch", "main")])
+                        .with_vars(&[("greeting", "hello")])
+                        .with_var_files(&["ci/vars/common.yml"])
+                        .with_team("main")
+                        .with_pipeline(&child)
+                        .to_step(),
+                ),
+        );
+
+        let files =
+            cook_pipeline_files(&pipeline, "ci/pipelines/parent.yml", &CookOptions::new()).unwrap();
+        assert_eq!(
+            files.keys().collect::<Vec<_>>(),
+            vec!["ci/pipelines/child.yml", "ci/pipelines/parent.yml"]
+        );
+        assert_eq!(
+            files["ci/pipelines/parent.yml"],
+            r#"jobs:
+- name: set-pipelines
+  plan:
+  - set_pipeline: self
+    file: ci/pipelines/parent.yml
+  - set_pipeline: child
+    file: ci/pipelines/child.yml
+    instance_vars:
+      branch: main
+    vars:
+      greeting: hello
+    var_files:
+    - ci/vars/common.yml
+    team: main
+"#
+        );
+        assert_eq!(
+            files["ci/pipelines/child.yml"],
+            cook_pipeline(&child).unwrap()
+        );
+    }
+
+    // https://concourse-ci.org/load-var-step.html
+    #[test]
+    fn load_var_step() {
+        let repo = Resource::new("repo", &mock_resource_type());
+        let image = Resource::new("image", &mock_resource_type());
+        let version = LoadVar::new("version", "repo/version")
+            .with_format(LoadVarFormat::Trim)
+            .with_reveal(true);
+
+        let pipeline = Pipeline::new().append(
+            Job::new("loading-vars")
+                .then(repo.as_get_resource().get())
+                .then(version.clone().to_step())
+                .then(
+                    image
+                        .as_put_resource()
+                        .with_params(&[("tag", version.var().to_string().as_str())])
+                        .put(),
+                ),
+        );
+
+        assert_eq!(
+            cook_round_trip(&pipeline),
+            r#"jobs:
+- name: loading-vars
+  plan:
+  - get: repo
+  - load_var: version
+    file: repo/version
+    format: trim
+    reveal: true
+  - put: image
+    params:
+      tag: ((.:version))
+resources:
+- name: image
+  type: mock
+- name: repo
+  type: mock
+resource_types:
+- name: mock
+  type: registry-image
+"#
+        );
+    }
+
+    #[test]
+    fn load_var_used_before_defined() {
+        let version = LoadVar::new("version", "repo/version");
+        let pipeline = Pipeline::new().append(
+            Job::new("loading-vars")
+                .then(
+                    Task::new()
+                        .with_name("print-version")
+                        .run(&Command::new("echo", &[version.var().to_string().as_str()]))
+                        .to_step(),
+                )
+                .then(version.clone().to_step()),
+        );
+
+        assert_eq!(
+            cook_pipeline(&pipeline).unwrap_err().to_string(),
+            "Cannot cook concourse configuration:\njobs[loading-vars].plan[0].task[print-version]: Local var '((.:version))' is not defined by a preceding load_var step or an enclosing across step"
+        );
+
+        // A var loaded by a hook is not visible after the hooked task.
+        let pipeline = Pipeline::new().append(
+            Job::new("loading-vars")
+                .then(
+                    Task::new()
+                        .with_name("build")
+                        .on_failure(version.clone().to_step())
+                        .to_step(),
+                )
+                .then(
+                    Task::new()
+                        .with_name("print-version")
+                        .run(&Command::new("echo", &[version.var().to_string().as_str()]))
+                        .to_step(),
+                ),
+        );
+
+        assert_eq!(
+            cook_pipeline(&pipeline).unwrap_err().to_string(),
+            "Cannot cook concourse configuration:\njobs[loading-vars].plan[1].task[print-version]: Local var '((.:version))' is not defined by a preceding load_var step or an enclosing across step"
+        );
+    }
+
+    // https://concourse-ci.org/do-step.html
+    #[test]
+    fn do_step() {
+        let status = Resource::new("status", &mock_resource_type());
+        let notify_scripts = Resource::new("notify-scripts", &mock_resource_type());
+        let pipeline = Pipeline::new().append(
+            Job::new("with-do").try_(Step::do_(&[
+                status
+                    .as_put_resource()
+                    .with_params(&[("state", "pending")])
+                    .put(),
+                Task::new()
+                    .with_name("notify")
+                    .with_inputs(&[&notify_scripts.as_task_input_resource()])
+                    .run(&Command::new("notify-scripts/notify.sh", &[]))
+                    .to_step(),
+            ])),
+        );
+
+        assert_eq!(
+            cook_round_trip(&pipeline),
+            r#"jobs:
+- name: with-do
+  plan:
+  - try:
+      do:
+      - put: status
+        params:
+          state: pending
+      - in_parallel:
+        - get: notify-scripts
+      - task: notify
+        config:
+          platform: linux
+          image_resource:
+            type: registry-image
+            source:
+              repository: busybox
+          run:
+            path: notify-scripts/notify.sh
+          inputs:
+          - name: notify-scripts
+resources:
+- name: notify-scripts
+  type: mock
+- name: status
+  type: mock
+resource_types:
+- name: mock
+  type: registry-image
+"#
+        );
+    }
+
+    // https://concourse-ci.org/across-step.html
+    #[test]
+    fn across_step() {
+        let pg_version =
+            AcrossVar::new("pg_version", &["13", "14"]).with_max_in_flight(MaxInFlight::Limit(2));
+        let os = AcrossVar::new("os", &["jammy", "rocky9"]).with_max_in_flight(MaxInFlight::All);
+        let pipeline = Pipeline::new().append(
+            Job::new("matrix").then(
+                Task::new()
+                    .with_name("test")
+                    .run(&Command::new(
+                        "make",
+                        &[
+                            "test",
+                            format!("PG_VERSION={}", pg_version.var()).as_str(),
+                            format!("OS={}", os.var()).as_str(),
+                        ],
+                    ))
+                    .to_step()
+                    .across(&[&pg_version, &os])
+                    .with_fail_fast(true),
+            ),
+        );
+
+        assert_eq!(
+            cook_round_trip(&pipeline),
+            r#"jobs:
+- name: matrix
+  plan:
+  - task: test
+    config:
+      platform: linux
+      image_resource:
+        type: registry-image
+        source:
+          repository: busybox
+      run:
+        path: make
+        args:
+        - test
+        - PG_VERSION=((.:pg_version))
+        - OS=((.:os))
+    across:
+    - var: pg_version
+      values:
+      - '13'
+      - '14'
+      max_in_flight: 2
+    - var: os
+      values:
+      - jammy
+      - rocky9
+      max_in_flight: all
+    fail_fast: true
 "#
         );
     }
+
+    #[test]
+    fn across_var_not_defined() {
+        let pg_version = AcrossVar::new("pg_version", &["13", "14"]);
+        let os = AcrossVar::new("os", &["jammy", "rocky9"]);
+        let pipeline = Pipeline::new().append(
+            Job::new("matrix").then(
+                Task::new()
+                    .with_name("test")
+                    .run(&Command::new(
+                        "make",
+                        &[format!("OS={}", os.var()).as_str()],
+                    ))
+                    .to_step()
+                    .across(&[&pg_version]),
+            ),
+        );
+
+        assert_eq!(
+            cook_pipeline(&pipeline).unwrap_err().to_string(),
+            "Cannot cook concourse configuration:\njobs[matrix].plan[0].task[test]: Local var '((.:os))' is not defined by a preceding load_var step or an enclosing across step"
+        );
+    }
+
+    // https://concourse-ci.org/modifier-and-hook-steps.html
+    #[test]
+    fn step_modifiers_and_hooks() {
+        let repo = Resource::new("repo", &mock_resource_type());
+        let status = Resource::new("status", &mock_resource_type());
+        let notify_scripts = Resource::new("notify-scripts", &mock_resource_type());
+        let pipeline = Pipeline::new().append(
+            Job::new("modified")
+                .then(
+                    repo.as_get_resource()
+                        .get()
+                        .with_timeout(Duration::minutes(5))
+                        .with_attempts(3),
+                )
+                .then(
+                    Task::new()
+                        .with_name("build")
+                        .run(&Command::new("make", &[]))
+                        .to_step()
+                        .with_timeout(Duration::hours(1) + Duration::minutes(30))
+                        .with_tags(&["large"])
+                        .ensure(
+                            status
+                                .as_put_resource()
+                                .with_params(&[("state", "done")])
+                                .put()
+                                .on_error(Step::try_(status.as_put_resource().put())),
+                        ),
+                )
+                .on_failure(Step::do_(&[
+                    status
+                        .as_put_resource()
+                        .with_params(&[("state", "failure")])
+                        .put(),
+                    Task::new()
+                        .with_name("notify")
+                        .with_inputs(&[&notify_scripts.as_task_input_resource()])
+                        .run(&Command::new("notify-scripts/notify.sh", &[]))
+                        .to_step(),
+                ])),
+        );
+
+        assert_eq!(
+            cook_round_trip(&pipeline),
+            r#"jobs:
+- name: modified
+  plan:
+  - get: repo
+    timeout: 5m
+    attempts: 3
+  - task: build
+    config:
+      platform: linux
+      image_resource:
+        type: registry-image
+        source:
+          repository: busybox
+      run:
+        path: make
+    ensure:
+      put: status
+      params:
+        state: done
+      on_error:
+        try:
+          put: status
+    timeout: 1h30m
+    tags:
+    - large
+  on_failure:
+    do:
+    - put: status
+      params:
+        state: failure
+    - in_parallel:
+      - get: notify-scripts
+    - task: notify
+      config:
+        platform: linux
+        image_resource:
+          type: registry-image
+          source:
+            repository: busybox
+        run:
+          path: notify-scripts/notify.sh
+        inputs:
+        - name: notify-scripts
+resources:
+- name: notify-scripts
+  type: mock
+- name: repo
+  type: mock
+- name: status
+  type: mock
+resource_types:
+- name: mock
+  type: registry-image
+"#
+        );
+    }
+
+    // https://concourse-ci.org/jobs.html
+    #[test]
+    fn job_configuration() {
+        let pipeline = Pipeline::new().append(
+            Job::new("deploy")
+                .with_old_name("deploy-old")
+                .with_serial_groups(&["deployments"])
+                .with_max_in_flight(1)
+                .with_build_log_retention(
+                    BuildLogRetention::new()
+                        .with_builds(50)
+                        .with_days(14)
+                        .with_minimum_succeeded_builds(5),
+                )
+                .with_disable_manual_trigger(true)
+                .with_interruptible(true)
+                .then(
+                    Task::new()
+                        .with_name("deploy")
+                        .run(&Command::new("./deploy.sh", &[]))
+                        .to_step(),
+                ),
+        );
+
+        assert_eq!(
+            cook_round_trip(&pipeline),
+            r#"jobs:
+- name: deploy
+  old_name: deploy-old
+  serial_groups:
+  - deployments
+  max_in_flight: 1
+  build_log_retention:
+    builds: 50
+    days: 14
+    minimum_succeeded_builds: 5
+  disable_manual_trigger: true
+  interruptible: true
+  plan:
+  - task: deploy
+    config:
+      platform: linux
+      image_resource:
+        type: registry-image
+        source:
+          repository: busybox
+      run:
+        path: ./deploy.sh
+"#
+        );
+    }
+
+    #[test]
+    fn serial_job_with_max_in_flight() {
+        let pipeline = Pipeline::new().append(
+            Job::new("job")
+                .with_serial(true)
+                .with_max_in_flight(2)
+                .then(Task::new().with_name("simple-task").to_step()),
+        );
+
+        assert_eq!(
+            cook_pipeline(&pipeline).unwrap_err().to_string(),
+            "Cannot cook concourse configuration:\njobs[job]: Job 'job' cannot be serial with max_in_flight 2"
+        );
+    }
+
+    // Concourse runs serial groups one build at a time whatever the
+    // max_in_flight, so both can be set.
+    #[test]
+    fn serial_groups_with_max_in_flight() {
+        let pipeline = Pipeline::new().append(
+            Job::new("job")
+                .with_serial_groups(&["deployments"])
+                .with_max_in_flight(2)
+                .then(Task::new().with_name("simple-task").to_step()),
+        );
+
+        assert_eq!(
+            cook_pipeline(&pipeline).unwrap(),
+            r#"jobs:
+- name: job
+  serial_groups:
+  - deployments
+  max_in_flight: 2
+  plan:
+  - task: simple-task
+    config:
+      platform: linux
+      image_resource:
+        type: registry-image
+        source:
+          repository: busybox
+      run:
+        path: echo
+        args:
+        - hello, world!
+"#
+        );
+    }
+
+    // https://concourse-ci.org/pipelines.html#schema.group_config
+    #[test]
+    fn pipeline_groups() {
+        let job = |name: &str| {
+            Job::new(name).then(
+                Task::new()
+                    .with_name("simple-task")
+                    .run(&Command::new("echo", &[name]))
+                    .to_step(),
+            )
+        };
+        let unit = job("unit");
+        let integration = job("integration");
+        let deploy = job("deploy");
+        let cleanup = job("cleanup");
+
+        let pipeline = Pipeline::new()
+            .append(unit.clone())
+            .append(integration.clone())
+            .append(deploy.clone())
+            .append(cleanup.clone())
+            .with_group("test", &[&unit, &integration])
+            .with_group("release", &[&deploy]);
+
+        let groups = |options: &CookOptions| {
+            let yaml = cook_pipeline_with_options(&pipeline, options).unwrap();
+            yaml[yaml.find("groups:").unwrap()..].to_string()
+        };
+
+        assert_eq!(
+            groups(&CookOptions::new()),
+            r#"groups:
+- name: test
+  jobs:
+  - unit
+  - integration
+- name: release
+  jobs:
+  - deploy
+"#
+        );
+        assert_eq!(
+            groups(&CookOptions::new().with_catch_all_group(CatchAllGroup::All)),
+            r#"groups:
+- name: all
+  jobs:
+  - unit
+  - integration
+  - deploy
+  - cleanup
+- name: test
+  jobs:
+  - unit
+  - integration
+- name: release
+  jobs:
+  - deploy
+"#
+        );
+        assert_eq!(
+            groups(&CookOptions::new().with_catch_all_group(CatchAllGroup::Ungrouped)),
+            r#"groups:
+- name: test
+  jobs:
+  - unit
+  - integration
+- name: release
+  jobs:
+  - deploy
+- name: ungrouped
+  jobs:
+  - cleanup
+"#
+        );
+    }
+
+    #[test]
+    fn group_with_unknown_job() {
+        let unit = Job::new("unit").then(Task::new().with_name("simple-task").to_step());
+        let deploy = Job::new("deploy").then(Task::new().with_name("simple-task").to_step());
+        let pipeline = Pipeline::new()
+            .append(unit.clone())
+            .with_group("all", &[&unit, &deploy]);
+
+        assert_eq!(
+            cook_pipeline(&pipeline).unwrap_err().to_string(),
+            "Cannot cook concourse configuration:\ngroups[all]: Group 'all' references job 'deploy' that is not in the pipeline"
+        );
+    }
+
+    // https://concourse-ci.org/vars.html#var-sources
+    #[test]
+    fn var_sources() {
+        let vault = VarSource::vault(
+            "vault",
+            VaultConfig::new("https://vault.example.com:8200")
+                .with_path_prefix("/concourse")
+                .with_auth("approle", &[("role_id", "((role_id))")])
+                .with_login_timeout(Duration::seconds(30)),
+        );
+        let ssm = VarSource::ssm("ssm", AwsConfig::new("us-east-1"));
+        let dummy = VarSource::dummy("dummy", &[("greeting", "hello")]);
+
+        let pipeline = Pipeline::new()
+            .with_var_source(&vault)
+            .with_var_source(&ssm)
+            .with_var_source(&dummy)
+            .append(
+                Job::new("job").then(
+                    Task::new()
+                        .with_name("print-secrets")
+                        .run(&Command::new(
+                            "echo",
+                            &[
+                                vault.var("git").field("private_key").to_string().as_str(),
+                                ssm.var("token").to_string().as_str(),
+                                dummy.var("greeting").to_string().as_str(),
+                            ],
+                        ))
+                        .to_step(),
+                ),
+            );
+
+        assert_eq!(
+            cook_round_trip(&pipeline),
+            r#"jobs:
+- name: job
+  plan:
+  - task: print-secrets
+    config:
+      platform: linux
+      image_resource:
+        type: registry-image
+        source:
+          repository: busybox
+      run:
+        path: echo
+        args:
+        - ((vault:git.private_key))
+        - ((ssm:token))
+        - ((dummy:greeting))
+var_sources:
+- name: vault
+  type: vault
+  config:
+    url: https://vault.example.com:8200
+    path_prefix: /concourse
+    auth_backend: approle
+    auth_params:
+      role_id: ((role_id))
+    login_timeout: 30s
+- name: ssm
+  type: ssm
+  config:
+    region: us-east-1
+- name: dummy
+  type: dummy
+  config:
+    vars:
+      greeting: hello
+"#
+        );
+    }
+
+    #[test]
+    fn undeclared_var_source() {
+        let vault = VarSource::vault("vault", VaultConfig::new("https://vault.example.com"));
+        let pipeline = Pipeline::new().append(
+            Job::new("job").then(
+                Task::new()
+                    .with_name("print-secret")
+                    .run(&Command::new(
+                        "echo",
+                        &[vault.var("token").to_string().as_str()],
+                    ))
+                    .to_step(),
+            ),
+        );
+
+        assert_eq!(
+            cook_pipeline(&pipeline).unwrap_err().to_string(),
+            "Cannot cook concourse configuration:\njobs[job].plan[0].task[print-secret]: Var '((vault:token))' refers to var source 'vault' that is not declared in the pipeline"
+        );
+    }
+
+    // https://concourse-ci.org/resources.html
+    #[test]
+    fn resource_options() {
+        let repo = Resource::git("https://github.com/concourse/examples.git", "main")
+            .with_name("repo")
+            .with_check_every(CheckEvery::Interval(Duration::minutes(10)))
+            .with_check_timeout(Duration::minutes(2))
+            .with_tags(&["internal"])
+            .with_public(true)
+            .with_webhook_token(&Var::new("webhook_token"))
+            .with_expose_build_created_by(true);
+        let releases =
+            Resource::new("releases", &mock_resource_type()).with_check_every(CheckEvery::Never);
+        let pipeline = Pipeline::new().append(
+            Job::new("job")
+                .then(repo.as_get_resource().get())
+                .then(releases.as_get_resource().get()),
+        );
+
+        let yaml = cook_round_trip(&pipeline);
+        assert_eq!(
+            &yaml[yaml.find("resources:").unwrap()..],
+            r#"resources:
+- name: releases
+  type: mock
+  check_every: never
+- name: repo
+  type: git
+  icon: github
+  source:
+    branch: main
+    uri: https://github.com/concourse/examples.git
+  check_every: 10m
+  check_timeout: 2m
+  tags:
+  - internal
+  public: true
+  webhook_token: ((webhook_token))
+  expose_build_created_by: true
+resource_types:
+- name: mock
+  type: registry-image
+"#
+        );
+    }
+
+    // https://github.com/concourse/git-resource#source-configuration
+    // https://github.com/concourse/registry-image-resource#source-configuration
+    // https://github.com/concourse/time-resource#source-configuration
+    #[test]
+    fn typed_sources() {
+        let docs = GitSource::new("https://github.com/concourse/docs")
+            .with_branch("master")
+            .with_paths(&["lit/"])
+            .with_ignore_paths(&["lit/release-notes/"])
+            .with_private_key(&Var::new("docs_deploy_key"))
+            .with_tag_filter("v*")
+            .with_fetch_tags(true)
+            .with_depth(1)
+            .to_resource();
+        let image = RegistryImageSource::new("concourse/concourse")
+            .with_tag("7.9")
+            .with_credentials("ci", &Var::new("docker_hub_password"))
+            .with_platform("linux", "arm64")
+            .to_resource()
+            .with_source(&[("variant", "ubuntu")]);
+        let nightly = TimeSource::new()
+            .with_range("1:00 AM", "2:00 AM")
+            .with_location("Europe/Amsterdam")
+            .with_days(&[Day::Monday, Day::Friday])
+            .to_resource()
+            .with_name("nightly");
+        let pipeline = Pipeline::new().append(
+            Job::new("job")
+                .then(nightly.as_get_resource().with_trigger(true).get())
+                .then(docs.as_get_resource().get())
+                .then(image.as_get_resource().get()),
+        );
+
+        let yaml = cook_round_trip(&pipeline);
+        assert_eq!(
+            &yaml[yaml.find("resources:").unwrap()..],
+            r#"resources:
+- name: concourse/concourse
+  type: registry-image
+  icon: docker
+  source:
+    password: ((docker_hub_password))
+    platform:
+      os: linux
+      architecture: arm64
+    repository: concourse/concourse
+    tag: '7.9'
+    username: ci
+    variant: ubuntu
+- name: docs.master
+  type: git
+  icon: github
+  source:
+    branch: master
+    depth: 1
+    fetch_tags: true
+    ignore_paths:
+    - lit/release-notes/
+    paths:
+    - lit/
+    private_key: ((docs_deploy_key))
+    tag_filter: v*
+    uri: https://github.com/concourse/docs
+- name: nightly
+  type: time
+  icon: clock-outline
+  source:
+    days:
+    - Monday
+    - Friday
+    location: Europe/Amsterdam
+    start: 1:00 AM
+    stop: 2:00 AM
+"#
+        );
+    }
+
+    // https://concourse-ci.org/config-basics.html#schema.value
+    #[test]
+    fn structured_values() {
+        let image = Resource::registry_image("example/app").with_source(&[(
+            "aws",
+            Value::map(&[
+                ("aws_region", Value::from("eu-west-1")),
+                ("aws_role_arns", Value::list(&["arn:aws:iam::1:role/ci"])),
+            ]),
+        )]);
+        let pipeline = Pipeline::new().append(
+            Job::new("job")
+                .then(
+                    Task::new()
+                        .with_name("build")
+                        .mutate_task_config(|task_config| {
+                            task_config
+                                .with_env(&[("JOBS", Value::from(4)), ("DEBUG", Value::from(true))])
+                                .run(&Command::new("make", &[]))
+                        })
+                        .with_params(&[("TARGETS", vec!["linux", "darwin"])])
+                        .to_step(),
+                )
+                .then(
+                    image
+                        .as_put_resource()
+                        .with_params(&[
+                            ("image", Value::from("image/image.tar")),
+                            ("additional_tags", Value::from("image/tags")),
+                            ("bump_aliases", Value::from(true)),
+                        ])
+                        .put(),
+                ),
+        );
+
+        let yaml = cook_round_trip(&pipeline);
+        assert_eq!(
+            &yaml[yaml.find("      run:").unwrap()..],
+            r#"      run:
+        path: make
+      params:
+        DEBUG: true
+        JOBS: 4
+    params:
+      TARGETS:
+      - linux
+      - darwin
+  - put: example/app
+    params:
+      additional_tags: image/tags
+      bump_aliases: true
+      image: image/image.tar
+resources:
+- name: example/app
+  type: registry-image
+  icon: docker
+  source:
+    aws:
+      aws_region: eu-west-1
+      aws_role_arns:
+      - arn:aws:iam::1:role/ci
+    repository: example/app
+"#
+        );
+    }
+
+    // https://concourse-ci.org/resource-types.html
+    #[test]
+    fn resource_type_configuration() {
+        let bucket = ResourceTypes::new("bucket", ResourceTypes::RegistryImage)
+            .with_source(&[("repository", "example/bucket-resource")])
+            .with_privileged(true)
+            .with_check_every(CheckEvery::Interval(Duration::hours(1)))
+            .with_tags(&["internal"])
+            .with_defaults(&[("region", "eu-west-1"), ("endpoint", "s3.internal")]);
+        let releases = Resource::new("releases", &bucket)
+            .with_source(&[("bucket", "releases"), ("region", "eu-west-1")]);
+        let artifacts = Resource::new("artifacts", &bucket)
+            .with_source(&[("bucket", "artifacts"), ("region", "us-east-1")]);
+        let pipeline = Pipeline::new().append(
+            Job::new("job")
+                .then(releases.as_get_resource().get())
+                .then(artifacts.as_put_resource().put()),
+        );
+
+        let yaml = cook_round_trip(&pipeline);
+        assert_eq!(
+            &yaml[yaml.find("resources:").unwrap()..],
+            r#"resources:
+- name: artifacts
+  type: bucket
+  source:
+    bucket: artifacts
+    endpoint: s3.internal
+    region: us-east-1
+- name: releases
+  type: bucket
+  source:
+    bucket: releases
+    endpoint: s3.internal
+    region: eu-west-1
+resource_types:
+- name: bucket
+  type: registry-image
+  source:
+    repository: example/bucket-resource
+  privileged: true
+  check_every: 1h
+  tags:
+  - internal
+  defaults:
+    endpoint: s3.internal
+    region: eu-west-1
+"#
+        );
+
+        let yaml = cook_pipeline_with_options(
+            &pipeline,
+            &CookOptions::new().with_resource_type_defaults(ResourceTypeDefaults::Drop),
+        )
+        .unwrap();
+        assert_eq!(
+            &yaml[yaml.find("resources:").unwrap()..yaml.find("resource_types:").unwrap()],
+            r#"resources:
+- name: artifacts
+  type: bucket
+  source:
+    bucket: artifacts
+    region: us-east-1
+- name: releases
+  type: bucket
+  source:
+    bucket: releases
+"#
+        );
+    }
+
+    // Defaults neither override nor miss fields of typed sources.
+    #[test]
+    fn resource_type_defaults_with_typed_source() {
+        let semver = semver_type().with_defaults(&[
+            ("branch", "main"),
+            ("file", "version"),
+            ("initial_version", "0.1.0"),
+        ]);
+        let version = Resource::new("version", &semver).with_typed_source(TypedSource::Semver(
+            SemverSource::git("git@github.com:example/app.git", "version", "version"),
+        ));
+        let pipeline =
+            Pipeline::new().append(Job::new("job").then(version.as_get_resource().get()));
+
+        let yaml = cook_round_trip(&pipeline);
+        assert_eq!(
+            &yaml[yaml.find("resources:").unwrap()..yaml.find("resource_types:").unwrap()],
+            r#"resources:
+- name: version
+  type: semver
+  source:
+    branch: version
+    driver: git
+    file: version
+    initial_version: 0.1.0
+    uri: git@github.com:example/app.git
+"#
+        );
+
+        let yaml = cook_pipeline_with_options(
+            &pipeline,
+            &CookOptions::new().with_resource_type_defaults(ResourceTypeDefaults::Drop),
+        )
+        .unwrap();
+        assert_eq!(
+            &yaml[yaml.find("resources:").unwrap()..yaml.find("resource_types:").unwrap()],
+            r#"resources:
+- name: version
+  type: semver
+  source:
+    branch: version
+    driver: git
+    uri: git@github.com:example/app.git
+"#
+        );
+    }
+
+    // Typed sources and params only need the name of the resource type to
+    // match, so the type itself can still be configured.
+    #[test]
+    fn typed_source_of_configured_type() {
+        let version = Resource::new("version", &semver_type().with_privileged(true))
+            .with_typed_source(TypedSource::Semver(SemverSource::git(
+                "git@github.com:example/app.git",
+                "version",
+                "version",
+            )));
+        let pipeline = Pipeline::new().append(
+            Job::new("release").then(
+                version
+                    .as_put_resource()
+                    .with_typed_params(&SemverParams::bump(SemverBump::Patch))
+                    .put(),
+            ),
+        );
+
+        assert_eq!(
+            cook_round_trip(&pipeline),
+            r#"jobs:
+- name: release
+  plan:
+  - put: version
+    params:
+      bump: patch
+resources:
+- name: version
+  type: semver
+  source:
+    branch: version
+    driver: git
+    file: version
+    uri: git@github.com:example/app.git
+resource_types:
+- name: semver
+  type: registry-image
+  source:
+    repository: concourse/semver-resource
+  privileged: true
+"#
+        );
+    }
+
+    // https://github.com/concourse/semver-resource
+    // https://github.com/concourse/pool-resource
+    #[test]
+    fn community_resource_types() {
+        let version = SemverSource::git("git@github.com:example/app.git", "version", "version")
+            .with_private_key(&Var::new("deploy_key"))
+            .with_initial_version("1.0.0")
+            .to_resource();
+        let envs = PoolSource::new("git@github.com:example/locks.git", "main", "envs")
+            .with_retry_delay(Duration::seconds(30))
+            .to_resource();
+        let artifacts = S3Source::regexp("artifacts", "app-(.*).tgz")
+            .with_credentials(&Var::new("aws_access_key"), &Var::new("aws_secret_key"))
+            .with_region_name("eu-west-1")
+            .to_resource();
+        let release = GithubReleaseSource::new("example", "app")
+            .with_access_token(&Var::new("github_token"))
+            .to_resource();
+        let slack = SlackNotificationSource::new(&Var::new("slack_webhook")).to_resource();
+        let mock = MockSource::new().with_mirror_self(true).to_resource();
+
+        let pipeline = Pipeline::new().append(
+            Job::new("release")
+                .then(
+                    version
+                        .as_put_resource()
+                        .with_typed_params(&SemverParams::bump(SemverBump::Minor))
+                        .put(),
+                )
+                .then(
+                    envs.as_put_resource()
+                        .with_typed_params(&PoolAction::Acquire)
+                        .put(),
+                )
+                .then(
+                    artifacts
+                        .as_put_resource()
+                        .with_typed_params(&S3Params::new("build/app-*.tgz").with_acl("private"))
+                        .put(),
+                )
+                .then(
+                    release
+                        .as_put_resource()
+                        .with_typed_params(
+                            &GithubReleaseParams::new("version/version", "version/version")
+                                .with_tag_prefix("v")
+                                .with_globs(&["build/app-*.tgz"]),
+                        )
+                        .put(),
+                )
+                .then(
+                    mock.as_put_resource()
+                        .with_typed_params(&MockParams::new().with_version("done"))
+                        .put(),
+                )
+                .then(
+                    envs.as_put_resource()
+                        .with_typed_params(&PoolAction::Release(String::from("envs")))
+                        .put(),
+                )
+                .on_failure(
+                    slack
+                        .as_put_resource()
+                        .with_typed_params(
+                            &SlackNotificationParams::text("Release failed").with_channel("#ci"),
+                        )
+                        .put(),
+                ),
+        );
+
+        assert_eq!(
+            cook_round_trip(&pipeline),
+            r#"jobs:
+- name: release
+  plan:
+  - put: version
+    params:
+      bump: minor
+  - put: envs
+    params:
+      acquire: true
+  - put: artifacts
+    params:
+      acl: private
+      file: build/app-*.tgz
+  - put: app-release
+    params:
+      globs:
+      - build/app-*.tgz
+      name: version/version
+      tag: version/version
+      tag_prefix: v
+  - put: mock
+    params:
+      version: done
+  - put: envs
+    params:
+      release: envs
+  on_failure:
+    put: slack
+    params:
+      channel: '#ci'
+      text: Release failed
+resources:
+- name: app-release
+  type: github-release
+  icon: github
+  source:
+    access_token: ((github_token))
+    owner: example
+    repository: app
+- name: artifacts
+  type: s3
+  icon: bucket
+  source:
+    access_key_id: ((aws_access_key))
+    bucket: artifacts
+    regexp: app-(.*).tgz
+    region_name: eu-west-1
+    secret_access_key: ((aws_secret_key))
+- name: envs
+  type: pool
+  icon: lock
+  source:
+    branch: main
+    pool: envs
+    retry_delay: 30s
+    uri: git@github.com:example/locks.git
+- name: mock
+  type: mock
+  source:
+    mirror_self: true
+- name: slack
+  type: slack-notification
+  icon: slack
+  source:
+    url: ((slack_webhook))
+- name: version
+  type: semver
+  icon: tag
+  source:
+    branch: version
+    driver: git
+    file: version
+    initial_version: 1.0.0
+    private_key: ((deploy_key))
+    uri: git@github.com:example/app.git
+resource_types:
+- name: github-release
+  type: registry-image
+  source:
+    repository: concourse/github-release-resource
+- name: mock
+  type: registry-image
+  source:
+    repository: concourse/mock-resource
+- name: pool
+  type: registry-image
+  source:
+    repository: concourse/pool-resource
+- name: s3
+  type: registry-image
+  source:
+    repository: concourse/s3-resource
+- name: semver
+  type: registry-image
+  source:
+    repository: concourse/semver-resource
+- name: slack-notification
+  type: registry-image
+  source:
+    repository: cfcommunity/slack-notification-resource
+"#
+        );
+    }
+
+    // https://concourse-ci.org/get-step.html
+    #[test]
+    fn get_step_params_and_passed() {
+        let repo = Resource::git("https://github.com/concourse/examples.git", "").with_name("repo");
+        let image = Resource::registry_image("golang");
+        let unit = Job::new("unit").then(repo.as_get_resource().with_trigger(true).get());
+        let deploy = Job::new("deploy")
+            .then(
+                repo.as_get_resource()
+                    .with_passed(&[&unit])
+                    .with_typed_params(
+                        &GitGetParams::new()
+                            .with_depth(1)
+                            .with_submodules(Submodules::Paths(vec![String::from("vendor")])),
+                    )
+                    .get()
+                    .with_timeout(Duration::minutes(5))
+                    .with_tags(&["internal"]),
+            )
+            .then(
+                image
+                    .as_get_resource()
+                    .with_pinned_version(&RegistryImageVersion::new("sha256:abc"))
+                    .with_typed_params(&RegistryImageGetParams::new().with_format(ImageFormat::Oci))
+                    .get(),
+            );
+        let pipeline = Pipeline::new().append(unit).append(deploy);
+
+        let yaml = cook_round_trip(&pipeline);
+        assert_eq!(
+            &yaml[..yaml.find("resources:").unwrap()],
+            r#"jobs:
+- name: unit
+  plan:
+  - get: repo
+    trigger: true
+- name: deploy
+  plan:
+  - get: repo
+    passed:
+    - unit
+    params:
+      depth: 1
+      submodules:
+      - vendor
+    timeout: 5m
+    tags:
+    - internal
+  - get: golang
+    version:
+      digest: sha256:abc
+    params:
+      format: oci
+"#
+        );
+    }
+
+    // https://concourse-ci.org/get-step.html#schema.get.passed
+    #[test]
+    fn passed_job_without_resource() {
+        let repo = Resource::git("https://github.com/concourse/examples.git", "").with_name("repo");
+        let lint = Job::new("lint").then(Task::new().with_name("simple-task").to_step());
+        let deploy = Job::new("deploy").then(
+            repo.as_get_resource()
+                .with_passed(&[&lint])
+                .with_pinned_version(&GitVersion::new("3f2a1b"))
+                .get(),
+        );
+
+        assert_eq!(
+            cook_pipeline(&Pipeline::new().append(lint.clone()).append(deploy.clone()))
+                .unwrap_err()
+                .to_string(),
+            "Cannot cook concourse configuration:\njobs[deploy].plan[0].get[repo]: Resource 'repo' must pass job 'lint' that never gets or puts it"
+        );
+        assert_eq!(
+            cook_pipeline(&Pipeline::new().append(deploy))
+                .unwrap_err()
+                .to_string(),
+            "Cannot cook concourse configuration:\njobs[deploy].plan[0].get[repo]: Resource 'repo' must pass job 'lint' that is not in the pipeline"
+        );
+    }
+
+    // Every problem is reported with the path of the step it was found in.
+    #[test]
+    fn validation_diagnostics() {
+        let repo = Resource::git("https://github.com/concourse/examples.git", "").with_name("repo");
+        let fork = Resource::git("https://github.com/example/examples.git", "").with_name("repo");
+        let unit = Job::new("unit").then(repo.as_get_resource().get());
+        let build = Job::new("build").parallel(&[
+            fork.as_get_resource()
+                .with_passed(&[&Job::new("lint")])
+                .get(),
+            Task::new()
+                .with_name("compile")
+                .mutate_task_config(|task_config| task_config.with_inputs(vec![Input::new("src")]))
+                .to_step(),
+        ]);
+        let pipeline = Pipeline::new()
+            .append(unit.clone())
+            .append(build)
+            .append(unit);
+
+        let expected = vec![
+            "jobs[unit]: Job 'unit' is defined more than once",
+            r#"jobs[build].plan[0].in_parallel[0].get[repo]: Resource 'repo' has conflicting definitions:
+name: repo
+type: git
+icon: github
+source:
+  uri: https://github.com/concourse/examples.git
+---
+name: repo
+type: git
+icon: github
+source:
+  uri: https://github.com/example/examples.git
+"#,
+            "jobs[build].plan[0].in_parallel[0].get[repo]: Resource 'repo' must pass job 'lint' that is not in the pipeline",
+            "jobs[build].plan[0].in_parallel[1].task[compile]: Input 'src' is not produced by any preceding step",
+        ];
+        assert_eq!(
+            validate(&pipeline)
+                .iter()
+                .map(|diagnostic| diagnostic.to_string())
+                .collect::<Vec<_>>(),
+            expected
+        );
+
+        // Renamed resources are checked by the names they are renamed to.
+        let options = CookOptions::new().with_resource_dedup(ResourceDedup::Rename);
+        assert_eq!(
+            cook_pipeline_with_options(&pipeline, &options)
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "Cannot cook concourse configuration:\n{}\n{}\n{}",
+                expected[0],
+                "jobs[build].plan[0].in_parallel[0].get[repo]: Resource 'repo-2' must pass job 'lint' that is not in the pipeline",
+                expected[3]
+            )
+        );
+
+        // Checks that used to stop cook at the first failure are reported too.
+        let version = LoadVar::new("version", "repo/version");
+        let job = Job::new("job")
+            .then(Task::new().with_name("job-task-2").to_step())
+            .then(
+                Task::new()
+                    .run(&Command::new("echo", &[version.var().to_string().as_str()]))
+                    .to_step(),
+            );
+        let pipeline = Pipeline::new()
+            .append(job.clone())
+            .with_group("all", &[&job, &Job::new("deploy")]);
+        assert_eq!(
+            validate(&pipeline)
+                .iter()
+                .map(|diagnostic| diagnostic.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "jobs[job]: Generated task name 'job-task-2' collides with another task, name the tasks explicitly",
+                "groups[all]: Group 'all' references job 'deploy' that is not in the pipeline",
+                "jobs[job].plan[1].task[job-task-2]: Local var '((.:version))' is not defined by a preceding load_var step or an enclosing across step",
+            ]
+        );
+    }
+
+    // Resources with the same name must have the same definition, unless cook
+    // is allowed to rename them.
+    #[test]
+    fn conflicting_resources() {
+        let repo = Resource::git("https://github.com/concourse/examples.git", "");
+        let fork = Resource::git("https://github.com/example/examples.git", "");
+        let pipeline = Pipeline::new().append(
+            Job::new("job")
+                .then(repo.as_get_resource().get())
+                .then(fork.as_put_resource().put())
+                .then(
+                    Task::new()
+                        .with_name("compare")
+                        .with_inputs(&[&fork.as_task_input_resource()])
+                        .to_step(),
+                ),
+        );
+
+        assert_eq!(
+            cook_pipeline(&pipeline).unwrap_err().to_string(),
+            r#"Cannot cook concourse configuration:
+jobs[job].plan[1].put[examples]: Resource 'examples' has conflicting definitions:
+name: examples
+type: git
+icon: github
+source:
+  uri: https://github.com/concourse/examples.git
+---
+name: examples
+type: git
+icon: github
+source:
+  uri: https://github.com/example/examples.git
+"#
+        );
+
+        let options = CookOptions::new().with_resource_dedup(ResourceDedup::Rename);
+        assert_eq!(
+            cook_pipeline_with_options(&pipeline, &options).unwrap(),
+            r#"jobs:
+- name: job
+  plan:
+  - get: examples
+  - put: examples
+    resource: examples-2
+  - task: compare
+    config:
+      platform: linux
+      image_resource:
+        type: registry-image
+        source:
+          repository: busybox
+      run:
+        path: echo
+        args:
+        - hello, world!
+      inputs:
+      - name: examples
+resources:
+- name: examples
+  type: git
+  icon: github
+  source:
+    uri: https://github.com/concourse/examples.git
+- name: examples-2
+  type: git
+  icon: github
+  source:
+    uri: https://github.com/example/examples.git
+"#
+        );
+
+        // The task input is fetched from the renamed resource, not from the
+        // resource that was fetched under the same name before.
+        let pipeline = Pipeline::new().append(
+            Job::new("job").then(repo.as_get_resource().get()).then(
+                Task::new()
+                    .with_name("compare")
+                    .with_inputs(&[&fork.as_task_input_resource()])
+                    .to_step(),
+            ),
+        );
+        let yaml = cook_pipeline_with_options(&pipeline, &options).unwrap();
+        assert_eq!(
+            &yaml[..yaml.find("  - task:").unwrap()],
+            r#"jobs:
+- name: job
+  plan:
+  - get: examples
+  - in_parallel:
+    - get: examples
+      resource: examples-2
+"#
+        );
+
+        // The renamed resource is not the one the passed job gets.
+        let a = Job::new("a").then(
+            Resource::git("https://a/repo.git", "main")
+                .as_get_resource()
+                .get(),
+        );
+        let b = Job::new("b").then(
+            Resource::git("https://b/repo.git", "main")
+                .as_get_resource()
+                .with_passed(&[&a])
+                .get(),
+        );
+        assert_eq!(
+            cook_pipeline_with_options(&Pipeline::new().append(a).append(b), &options)
+                .unwrap_err()
+                .to_string(),
+            "Cannot cook concourse configuration:\njobs[b].plan[0].get[repo.main]: Resource 'repo.main-2' must pass job 'a' that never gets or puts it"
+        );
+    }
+
+    #[test]
+    fn conflicting_resource_types() {
+        let version = Resource::new("version", &semver_type());
+        let release = Resource::new("release", &semver_type().with_privileged(true));
+        let pipeline = Pipeline::new().append(
+            Job::new("job")
+                .then(version.as_get_resource().get())
+                .then(release.as_put_resource().put()),
+        );
+
+        assert_eq!(
+            cook_pipeline(&pipeline).unwrap_err().to_string(),
+            r#"Cannot cook concourse configuration:
+jobs[job].plan[1].put[release]: Resource type 'semver' has conflicting definitions:
+name: semver
+type: registry-image
+source:
+  repository: concourse/semver-resource
+---
+name: semver
+type: registry-image
+source:
+  repository: concourse/semver-resource
+privileged: true
+"#
+        );
+    }
+
+    // Pipelines can be rendered in code review without uploading them.
+    #[test]
+    fn pipeline_graph() {
+        let repo = Resource::git("https://github.com/concourse/examples.git", "").with_name("repo");
+        let image = Resource::registry_image("example/app").with_name("image");
+        let unit = Job::new("unit").then(repo.as_get_resource().with_trigger(true).get());
+        let build = Job::new("build")
+            .then(
+                repo.as_get_resource()
+                    .with_trigger(true)
+                    .with_passed(&[&unit])
+                    .get(),
+            )
+            .then(image.as_put_resource().put());
+        let audit = Job::new("audit").then(repo.as_get_resource().get());
+        let graph = Graph::from_pipeline(&Pipeline::new().append(unit).append(build).append(audit))
+            .unwrap();
+
+        assert_eq!(
+            graph.to_dot(),
+            r#"digraph pipeline {
+  rankdir=LR;
+  "job:unit" [label="unit", shape=box];
+  "job:build" [label="build", shape=box];
+  "job:audit" [label="audit", shape=box];
+  "resource:image" [label="image", shape=ellipse];
+  "resource:repo" [label="repo", shape=ellipse];
+  "resource:repo" -> "job:unit";
+  "job:unit" -> "job:build" [label="repo"];
+  "job:build" -> "resource:image";
+  "resource:repo" -> "job:audit" [style=dashed];
+}
+"#
+        );
+        assert_eq!(
+            graph.to_mermaid(),
+            r#"flowchart LR
+  n0["unit"]
+  n1["build"]
+  n2["audit"]
+  n3(["image"])
+  n4(["repo"])
+  n4 --> n0
+  n0 -->|"repo"| n1
+  n1 --> n3
+  n4 -.-> n2
+"#
+        );
+        assert_eq!(graph.orphan_jobs(), vec!["audit"]);
+        assert!(graph.cycles().is_empty());
+
+        let mut lint = Job::new("lint");
+        let test = Job::new("test").then(repo.as_get_resource().with_passed(&[&lint]).get());
+        lint = lint.then(repo.as_get_resource().with_passed(&[&test]).get());
+        let graph = Graph::from_pipeline(&Pipeline::new().append(lint).append(test)).unwrap();
+        assert_eq!(graph.cycles(), vec![vec!["lint", "test"]]);
+
+        // Pipelines that only cook with renamed resources can be graphed too.
+        let fork = Resource::git("https://github.com/example/examples.git", "").with_name("repo");
+        let pipeline = Pipeline::new()
+            .append(Job::new("unit").then(repo.as_get_resource().get()))
+            .append(Job::new("fork").then(fork.as_get_resource().get()));
+        assert!(Graph::from_pipeline(&pipeline).is_err());
+        let options = CookOptions::new().with_resource_dedup(ResourceDedup::Rename);
+        let graph = Graph::from_pipeline_with_options(&pipeline, &options).unwrap();
+        assert_eq!(
+            graph
+                .nodes()
+                .iter()
+                .map(|node| node.name())
+                .collect::<Vec<_>>(),
+            vec!["unit", "fork", "repo", "repo-2"]
+        );
+    }
+
+    // https://concourse-ci.org/put-step.html
+    #[test]
+    fn put_step_implicit_get() {
+        let repo = Resource::git("https://github.com/concourse/examples.git", "").with_name("repo");
+        let image = Resource::registry_image("example/app");
+        let pipeline = Pipeline::new().append(
+            Job::new("job")
+                .then(
+                    repo.as_put_resource()
+                        .with_params(&[("repository", "repo")])
+                        .with_inputs(PutInputs::Detect)
+                        .with_typed_get_params(&GitGetParams::new().with_depth(1))
+                        .put(),
+                )
+                .then(
+                    image
+                        .as_put_resource()
+                        .with_params(&[("image", "image/image.tar")])
+                        .with_inputs(PutInputs::List(vec![String::from("image")]))
+                        .with_no_get(true)
+                        .put(),
+                )
+                .then(
+                    Task::new()
+                        .with_name("smoke-test")
+                        .run(&Command::new("repo/smoke-test.sh", &[]))
+                        .with_inputs(&[
+                            &repo.as_task_input_resource(),
+                            &image.as_task_input_resource(),
+                        ])
+                        .to_step(),
+                ),
+        );
+
+        let yaml = cook_round_trip(&pipeline);
+        assert_eq!(
+            &yaml[..yaml.find("  - task:").unwrap()],
+            r#"jobs:
+- name: job
+  plan:
+  - put: repo
+    params:
+      repository: repo
+    inputs: detect
+    get_params:
+      depth: 1
+  - put: example/app
+    params:
+      image: image/image.tar
+    inputs:
+    - image
+    no_get: true
+  - in_parallel:
+    - get: example/app
+"#
+        );
+    }
+
+    // https://concourse-ci.org/task-step.html
+    // https://concourse-ci.org/tasks.html#schema.task-config.caches
+    #[test]
+    fn task_privileged_limits_and_caches() {
+        let pipeline = Pipeline::new().append(
+            Job::new("job").then(
+                Task::new()
+                    .with_name("build")
+                    .mutate_task_config(|task_config| {
+                        task_config
+                            .with_caches(&["gocache", "vendor"])
+                            .run(&Command::new("make", &[]))
+                    })
+                    .with_privileged(true)
+                    .with_container_limits(
+                        ContainerLimits::new()
+                            .with_cpu(512)
+                            .with_memory(ByteSize::gigabytes(2)),
+                    )
+                    .to_step(),
+            ),
+        );
+
+        assert_eq!(
+            cook_round_trip(&pipeline),
+            r#"jobs:
+- name: job
+  plan:
+  - task: build
+    config:
+      platform: linux
+      image_resource:
+        type: registry-image
+        source:
+          repository: busybox
+      run:
+        path: make
+      caches:
+      - path: gocache
+      - path: vendor
+    privileged: true
+    container_limits:
+      cpu: 512
+      memory: 2GB
+"#
+        );
+        assert_eq!(ByteSize::megabytes(1536).to_string(), "1536MB");
+        assert_eq!(ByteSize::bytes(1000).to_string(), "1000");
+    }
+
+    // https://concourse-ci.org/tasks.html
+    #[test]
+    fn task_config_rootfs_uri_and_run_options() {
+        let repo = Resource::git("https://github.com/concourse/examples.git", "").with_name("repo");
+        let pipeline = Pipeline::new().append(
+            Job::new("job").then(repo.as_get_resource().get()).then(
+                Task::new()
+                    .with_name("build")
+                    .mutate_task_config(|task_config| {
+                        task_config
+                            .with_rootfs_uri("docker:///golang#1.17")
+                            .run(
+                                &Command::new("make", &["build"])
+                                    .with_dir("repo")
+                                    .with_user("root"),
+                            )
+                            .with_inputs(vec![
+                                Input::new("repo"),
+                                Input::new("cache").with_optional(true),
+                            ])
+                            .with_outputs(vec![Output::new("binary").with_path("repo/bin")])
+                            .with_container_limits(
+                                ContainerLimits::new().with_memory(ByteSize::megabytes(512)),
+                            )
+                    })
+                    .to_step(),
+            ),
+        );
+
+        let yaml = cook_round_trip(&pipeline);
+        assert_eq!(
+            &yaml[..yaml.find("resources:").unwrap()],
+            r#"jobs:
+- name: job
+  plan:
+  - get: repo
+  - task: build
+    config:
+      platform: linux
+      rootfs_uri: docker:///golang#1.17
+      run:
+        path: make
+        args:
+        - build
+        dir: repo
+        user: root
+      inputs:
+      - name: repo
+      - name: cache
+        optional: true
+      outputs:
+      - name: binary
+        path: repo/bin
+      container_limits:
+        memory: 512MB
+"#
+        );
+    }
+
+    // Inputs and outputs bound to resources are added to those configured.
+    #[test]
+    fn task_config_merged_with_task_resources() {
+        let repo = Resource::git("https://github.com/concourse/examples.git", "").with_name("repo");
+        let pipeline = Pipeline::new().append(
+            Job::new("job").then(
+                Task::new()
+                    .with_name("build")
+                    .with_inputs(&[&repo.as_task_input_resource()])
+                    .with_outputs(&[&TaskResource::output("binary")])
+                    .mutate_task_config(|task_config| {
+                        task_config
+                            .with_inputs(vec![Input::new("cache").with_optional(true)])
+                            .with_outputs(vec![Output::new("binary").with_path("repo/bin")])
+                    })
+                    .to_step(),
+            ),
+        );
+
+        let yaml = cook_round_trip(&pipeline);
+        assert_eq!(
+            &yaml[..yaml.find("resources:").unwrap()],
+            r#"jobs:
+- name: job
+  plan:
+  - in_parallel:
+    - get: repo
+  - task: build
+    config:
+      platform: linux
+      image_resource:
+        type: registry-image
+        source:
+          repository: busybox
+      run:
+        path: echo
+        args:
+        - hello, world!
+      inputs:
+      - name: cache
+        optional: true
+      - name: repo
+      outputs:
+      - name: binary
+        path: repo/bin
+"#
+        );
+    }
+
+    // Unnamed tasks get the same name every time the pipeline is cooked.
+    #[test]
+    fn deterministic_task_names() {
+        let build = Task::new().run(&Command::new("make", &["build"])).to_step();
+        let test = Task::new().run(&Command::new("make", &["test"])).to_step();
+        let pipeline = Pipeline::new().append(
+            Job::new("job")
+                .then(build.clone())
+                .then(test.clone().on_failure(Task::new().to_step())),
+        );
+
+        let yaml = cook_round_trip(&pipeline);
+        assert_eq!(yaml, cook_round_trip(&pipeline));
+        assert_eq!(
+            yaml.lines()
+                .filter(|line| line.contains("task:"))
+                .collect::<Vec<_>>(),
+            vec![
+                "  - task: job-task-1",
+                "  - task: job-task-2",
+                "      task: job-task-3"
+            ]
+        );
+
+        // Content hashes don't change when the plan is reordered.
+        let options = CookOptions::new().with_task_naming(TaskNaming::ContentHash);
+        let names = |pipeline: &Pipeline| {
+            let yaml = cook_pipeline_with_options(pipeline, &options).unwrap();
+            let mut names = yaml
+                .lines()
+                .filter_map(|line| line.split_once("task: ").map(|(_, name)| name.to_string()))
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+        let hashed =
+            names(&Pipeline::new().append(Job::new("job").then(build.clone()).then(test.clone())));
+        assert_eq!(
+            hashed,
+            names(&Pipeline::new().append(Job::new("job").then(test).then(build)))
+        );
+        assert_eq!(hashed.len(), 2);
+        assert!(hashed.iter().all(|name| name.starts_with("task-")));
+        assert_ne!(hashed[0], hashed[1]);
+    }
+
+    #[test]
+    fn colliding_task_names() {
+        let build = Task::new().run(&Command::new("make", &[])).to_step();
+        let pipeline = Pipeline::new().append(
+            Job::new("job")
+                .then(Task::new().with_name("job-task-2").to_step())
+                .then(build.clone()),
+        );
+        assert_eq!(
+            cook_pipeline(&pipeline).unwrap_err().to_string(),
+            "Cannot cook concourse configuration:\njobs[job]: Generated task name 'job-task-2' collides with another task, name the tasks explicitly"
+        );
+
+        let pipeline = Pipeline::new().append(Job::new("job").then(build.clone()).then(build));
+        assert!(cook_pipeline_with_options(
+            &pipeline,
+            &CookOptions::new().with_task_naming(TaskNaming::ContentHash)
+        )
+        .unwrap_err()
+        .to_string()
+        .contains("jobs[job]: Generated task name 'task-"));
+    }
+
+    #[test]
+    fn parse_hand_written_pipeline() {
+        let yaml = r#"jobs:
+- name: deploy
+  plan:
+  - get: repo
+    trigger: true
+  - task: deploy
+    file: repo/ci/deploy.yml
+    on_failure:
+      put: notify
+      params:
+        text: Deploying to ((.:env)) failed
+    across:
+    - var: env
+      values:
+      - staging
+      - production
+      max_in_flight: 1
+resources:
+- name: notify
+  type: slack-notification
+  source:
+    url: ((slack_webhook))
+- name: repo
+  type: git
+  source:
+    uri: https://github.com/concourse/examples
+resource_types:
+- name: slack-notification
+  type: registry-image
+  source:
+    repository: cfcommunity/slack-notification-resource
+"#;
+        let pipeline = parse_pipeline(yaml).unwrap();
+        assert_eq!(cook_pipeline(&pipeline).unwrap(), yaml);
+    }
+
+    // Declared resources and types are kept, even if no step uses them.
+    #[test]
+    fn parse_unused_resources() {
+        let yaml = r#"jobs:
+- name: build
+  plan:
+  - get: repo
+resources:
+- name: repo
+  type: chained
+- name: spare
+  type: git
+  source:
+    uri: https://github.com/concourse/examples
+resource_types:
+- name: base
+  type: registry-image
+  source:
+    repository: example/base
+- name: chained
+  type: base
+- name: unused
+  type: registry-image
+  source:
+    repository: example/unused
+"#;
+        let pipeline = parse_pipeline(yaml).unwrap();
+        assert_eq!(cook_pipeline(&pipeline).unwrap(), yaml);
+        assert_eq!(cook_round_trip(&pipeline), yaml);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let yaml = r#"jobs:
+- name: test
+  serial: yes please
+  plan:
+  - get: repo
+    triger: true
+  - put: image
+  - task: unit
+    file: repo/ci/unit.yml
+    config:
+      platform: linux
+resources:
+- name: repo
+  type: git
+  source:
+    uri: https://github.com/concourse/examples
+- name: nightly
+  type: cron
+"#;
+        assert_eq!(
+            parse_pipeline(yaml).unwrap_err().to_string(),
+            r#"Cannot parse concourse configuration:
+resources[nightly].type: Resource type 'cron' is not declared in the pipeline
+jobs[test].serial: Expected a boolean
+jobs[test].plan[0].get[repo]: Unknown field 'triger'
+jobs[test].plan[1].put[image]: Resource 'image' is not declared in the pipeline
+jobs[test].plan[2].task[unit].config: Missing field 'run'
+jobs[test].plan[2].task[unit].config: Missing field 'image_resource' or 'rootfs_uri'
+jobs[test].plan[2].task[unit]: Only one of 'config', 'file' can be set"#
+        );
+    }
+
+    #[test]
+    fn parse_out_of_range_values() {
+        let yaml = r#"jobs:
+- name: test
+  plan:
+  - get: repo
+  - task: unit
+    file: repo/ci/unit.yml
+    container_limits:
+      memory: 99999999999GB
+resources:
+- name: repo
+  type: git
+  source:
+    uri: https://github.com/concourse/examples
+  check_every: 99999999999999999h
+"#;
+        assert_eq!(
+            parse_pipeline(yaml).unwrap_err().to_string(),
+            r#"Cannot parse concourse configuration:
+resources[repo].check_every: Expected 'never' or a duration
+jobs[test].plan[1].task[unit].container_limits.memory: Expected a size, e.g., '512MB'"#
+        );
+
+        // Go writes zero durations as '0'.
+        let pipeline = parse_pipeline(
+            &yaml
+                .replace("99999999999999999h", "0")
+                .replace("99999999999GB", "1GB"),
+        )
+        .unwrap();
+        assert!(cook_pipeline(&pipeline)
+            .unwrap()
+            .contains("check_every: 0s\n"));
+
+        // Go also accepts fractions and units below a millisecond.
+        for (duration, cooked) in [
+            ("1.5h", "1h30m"),
+            (".5m", "30s"),
+            ("500us", "500us"),
+            ("500µs", "500us"),
+            ("10ns", "10ns"),
+            ("1m0.0015s", "1m1ms500us"),
+        ] {
+            let pipeline = parse_pipeline(
+                &yaml
+                    .replace("99999999999999999h", duration)
+                    .replace("99999999999GB", "1GB"),
+            )
+            .unwrap();
+            assert!(cook_pipeline(&pipeline)
+                .unwrap()
+                .contains(format!("check_every: {}\n", cooked).as_str()));
+        }
+
+        for duration in ["1.h.5", "1.5", ".h", "10xs"] {
+            assert_eq!(
+                parse_pipeline(&yaml.replace("99999999999999999h", duration))
+                    .unwrap_err()
+                    .to_string()
+                    .lines()
+                    .nth(1),
+                Some("resources[repo].check_every: Expected 'never' or a duration")
+            );
+        }
+    }
 }
@@ -1,11 +1,30 @@
 use crate::job::Job;
 use crate::resource::Resource;
+use crate::resource::ResourceTypes;
+use crate::schema::to_config;
+use crate::schema::to_map;
+use crate::schema::Config;
 use crate::schema::Identifier;
+use crate::schema::Value;
 use crate::schema::Version;
 use crate::step::Step;
 use serde::ser::SerializeStruct;
 use serde::Serialize;
 use serde::Serializer;
+use std::collections::BTreeMap;
+
+// Params of a get step that only apply to a certain resource type, see
+// source::GitGetParams for an example.
+pub trait GetParams: Serialize {
+    fn resource_type(&self) -> ResourceTypes;
+}
+
+// A version of a certain resource type that a get step can be pinned to, see
+// source::GitVersion for an example.
+pub trait PinnedVersion {
+    fn resource_type(&self) -> ResourceTypes;
+    fn to_version(&self) -> Version;
+}
 
 #[derive(Debug, Clone)]
 pub struct Get {
@@ -13,12 +32,13 @@ pub struct Get {
     pub(crate) resource: Resource,
     pub(crate) version: Option<Version>,
     pub(crate) trigger: bool,
-    pub(crate) passed: Option<Vec<Job>>,
+    pub(crate) passed: Vec<Identifier>,
+    pub(crate) params: Config,
 }
 
 impl Serialize for Get {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let mut state = serializer.serialize_struct("GetStep", 5)?;
+        let mut state = serializer.serialize_struct("GetStep", 6)?;
         if !self.get.is_empty() {
             state.serialize_field("get", &self.get)?;
             state.serialize_field("resource", &self.resource.name())?;
@@ -35,15 +55,12 @@ impl Serialize for Get {
             state.serialize_field("trigger", &self.trigger)?;
         }
 
-        match self.passed.as_ref() {
-            Some(passed) => state.serialize_field(
-                "passed",
-                &passed
-                    .iter()
-                    .map(|job| job.name())
-                    .collect::<Vec<Identifier>>(),
-            )?,
-            None => { /* Do nothing. */ }
+        if !self.passed.is_empty() {
+            state.serialize_field("passed", &self.passed)?;
+        }
+
+        if !self.params.is_empty() {
+            state.serialize_field("params", &self.params)?;
         }
 
         state.end()
@@ -57,7 +74,8 @@ impl Get {
             resource: resource.clone(),
             version,
             trigger: resource.trigger(),
-            passed: None,
+            passed: vec![],
+            params: BTreeMap::new(),
         }
     }
 
@@ -71,8 +89,41 @@ impl Get {
         self
     }
 
-    pub fn with_passed(mut self, jobs: &[Job]) -> Self {
-        self.passed = Some(jobs.to_vec());
+    pub fn with_pinned_version<V: PinnedVersion>(mut self, version: &V) -> Self {
+        if !self.resource.type_.is_same_type(&version.resource_type()) {
+            panic!(
+                "The version of resource '{}' must be of type '{}', got '{}'",
+                self.resource.name(),
+                self.resource.type_,
+                version.resource_type()
+            );
+        }
+        self.version = Some(version.to_version());
+        self
+    }
+
+    // Only versions that went through all the given jobs are fetched. The jobs
+    // must get or put the same resource, which is checked by cook.
+    pub fn with_passed(mut self, jobs: &[&Job]) -> Self {
+        self.passed = jobs.iter().map(|job| job.name()).collect();
+        self
+    }
+
+    pub fn with_params<V: Into<Value> + Clone>(mut self, params: &[(&str, V)]) -> Self {
+        self.params = to_map(params);
+        self
+    }
+
+    pub fn with_typed_params<P: GetParams>(mut self, params: &P) -> Self {
+        if !self.resource.type_.is_same_type(&params.resource_type()) {
+            panic!(
+                "The params of resource '{}' must be of type '{}', got '{}'",
+                self.resource.name(),
+                self.resource.type_,
+                params.resource_type()
+            );
+        }
+        self.params = to_config(params);
         self
     }
 
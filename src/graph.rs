@@ -0,0 +1,310 @@
+use crate::cook::optimize_pipeline;
+use crate::cook::CookOptions;
+use crate::errors::Errors;
+use crate::pipeline::Pipeline;
+use crate::schema::Identifier;
+use crate::step::Step;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Node {
+    Job(Identifier),
+    Resource(Identifier),
+}
+
+impl Node {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Job(ref name) | Self::Resource(ref name) => name.as_str(),
+        }
+    }
+
+    fn dot_id(&self) -> String {
+        match self {
+            Self::Job(ref name) => format!("\"job:{}\"", name.replace('"', "\\\"")),
+            Self::Resource(ref name) => format!("\"resource:{}\"", name.replace('"', "\\\"")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EdgeKind {
+    // From a resource to the job getting it.
+    Get,
+    // From a job to the resource it puts.
+    Put,
+    // From the job in 'passed' to the job getting the resource.
+    Passed(Identifier),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub(crate) from: Node,
+    pub(crate) to: Node,
+    pub(crate) kind: EdgeKind,
+    // Whether new versions trigger the target job.
+    pub(crate) trigger: bool,
+}
+
+impl Edge {
+    pub fn from(&self) -> &Node {
+        &self.from
+    }
+
+    pub fn to(&self) -> &Node {
+        &self.to
+    }
+
+    pub fn kind(&self) -> &EdgeKind {
+        &self.kind
+    }
+
+    pub fn trigger(&self) -> bool {
+        self.trigger
+    }
+}
+
+// Jobs and resources of a pipeline and how versions flow between them, like
+// the pipeline view of the Concourse web UI.
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    pub(crate) nodes: Vec<Node>,
+    pub(crate) edges: Vec<Edge>,
+}
+
+impl Graph {
+    // Builds the graph of the pipeline as it is cooked, i.e., including the
+    // resources that are fetched implicitly for tasks.
+    pub fn from_pipeline(pipeline: &Pipeline) -> Result<Self, Errors> {
+        Self::from_pipeline_with_options(pipeline, &CookOptions::default())
+    }
+
+    pub fn from_pipeline_with_options(
+        pipeline: &Pipeline,
+        options: &CookOptions,
+    ) -> Result<Self, Errors> {
+        let pipeline = optimize_pipeline(pipeline, options)?;
+        let mut graph = Self::default();
+        for job in pipeline.jobs.iter() {
+            graph.add_node(Node::Job(job.name()));
+        }
+        for resource in pipeline.resources.iter() {
+            graph.add_node(Node::Resource(resource.name()));
+        }
+
+        for job in pipeline.jobs.iter() {
+            for step in job.steps().into_iter().flat_map(|step| step.walk()) {
+                match step {
+                    Step::Get(ref get_step) if get_step.passed.is_empty() => {
+                        graph.add_edge(Edge {
+                            from: Node::Resource(get_step.resource.name()),
+                            to: Node::Job(job.name()),
+                            kind: EdgeKind::Get,
+                            trigger: get_step.trigger,
+                        });
+                    }
+                    Step::Get(ref get_step) => {
+                        for passed in get_step.passed.iter() {
+                            graph.add_edge(Edge {
+                                from: Node::Job(passed.clone()),
+                                to: Node::Job(job.name()),
+                                kind: EdgeKind::Passed(get_step.resource.name()),
+                                trigger: get_step.trigger,
+                            });
+                        }
+                    }
+                    Step::Put(ref put_step) => {
+                        graph.add_edge(Edge {
+                            from: Node::Job(job.name()),
+                            to: Node::Resource(put_step.resource.name()),
+                            kind: EdgeKind::Put,
+                            trigger: false,
+                        });
+                    }
+                    _ => { /* Do nothing. */ }
+                }
+            }
+        }
+        Ok(graph)
+    }
+
+    fn add_node(&mut self, node: Node) {
+        if !self.nodes.contains(&node) {
+            self.nodes.push(node);
+        }
+    }
+
+    fn add_edge(&mut self, edge: Edge) {
+        // Jobs in 'passed' that are not in the pipeline still show up.
+        self.add_node(edge.from.clone());
+        self.add_node(edge.to.clone());
+        if !self.edges.contains(&edge) {
+            self.edges.push(edge);
+        }
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    fn jobs(&self) -> Vec<&Identifier> {
+        self.nodes
+            .iter()
+            .filter_map(|node| match node {
+                Node::Job(ref name) => Some(name),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Jobs that must pass each other through 'passed', so none of them can
+    // ever get a version. Each cycle lists its jobs in pipeline order.
+    pub fn cycles(&self) -> Vec<Vec<Identifier>> {
+        let mut downstream: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for edge in self.edges.iter() {
+            if let (Node::Job(ref from), Node::Job(ref to)) = (&edge.from, &edge.to) {
+                downstream.entry(from).or_default().insert(to);
+            }
+        }
+        let reachable = |start: &str| {
+            let mut visited = BTreeSet::new();
+            let mut pending = vec![start];
+            while let Some(job) = pending.pop() {
+                for next in downstream.get(job).into_iter().flatten() {
+                    if visited.insert(*next) {
+                        pending.push(next);
+                    }
+                }
+            }
+            visited
+        };
+        let reachable_of_jobs = self
+            .jobs()
+            .into_iter()
+            .map(|job| (job.as_str(), reachable(job)))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut cycles: Vec<Vec<Identifier>> = vec![];
+        for job in self.jobs() {
+            if !reachable_of_jobs[job.as_str()].contains(job.as_str())
+                || cycles.iter().any(|cycle| cycle.contains(job))
+            {
+                continue;
+            }
+            // Jobs that reach each other are in the same cycle.
+            cycles.push(
+                self.jobs()
+                    .into_iter()
+                    .filter(|other| {
+                        reachable_of_jobs[job.as_str()].contains(other.as_str())
+                            && reachable_of_jobs[other.as_str()].contains(job.as_str())
+                    })
+                    .cloned()
+                    .collect(),
+            );
+        }
+        cycles
+    }
+
+    // Jobs that no resource triggers and that are not connected to any other
+    // job through 'passed', so they only run when triggered manually.
+    pub fn orphan_jobs(&self) -> Vec<Identifier> {
+        self.jobs()
+            .into_iter()
+            .filter(|job| {
+                !self.edges.iter().any(|edge| {
+                    let is_job = |node: &Node| *node == Node::Job(job.to_string());
+                    match edge.kind {
+                        EdgeKind::Passed(_) => is_job(&edge.from) || is_job(&edge.to),
+                        EdgeKind::Get => edge.trigger && is_job(&edge.to),
+                        EdgeKind::Put => false,
+                    }
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    // Triggering edges are solid and the others are dashed, like in the web UI.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph pipeline {\n  rankdir=LR;\n");
+        for node in self.nodes.iter() {
+            let shape = match node {
+                Node::Job(_) => "box",
+                Node::Resource(_) => "ellipse",
+            };
+            let label = node.name().replace('"', "\\\"");
+            writeln!(
+                dot,
+                "  {} [label=\"{}\", shape={}];",
+                node.dot_id(),
+                label,
+                shape
+            )
+            .unwrap();
+        }
+        for edge in self.edges.iter() {
+            let mut attributes = vec![];
+            if let EdgeKind::Passed(ref resource) = edge.kind {
+                attributes.push(format!("label=\"{}\"", resource.replace('"', "\\\"")));
+            }
+            if edge.kind != EdgeKind::Put && !edge.trigger {
+                attributes.push("style=dashed".to_string());
+            }
+            write!(dot, "  {} -> {}", edge.from.dot_id(), edge.to.dot_id()).unwrap();
+            if !attributes.is_empty() {
+                write!(dot, " [{}]", attributes.join(", ")).unwrap();
+            }
+            dot.push_str(";\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    // Nodes get ids by position since names may contain characters that
+    // Mermaid doesn't accept in ids.
+    pub fn to_mermaid(&self) -> String {
+        let ids = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node, format!("n{}", i)))
+            .collect::<BTreeMap<_, _>>();
+        let label = |name: &str| format!("\"{}\"", name.replace('"', "#quot;"));
+
+        let mut mermaid = String::from("flowchart LR\n");
+        for node in self.nodes.iter() {
+            let name = label(node.name());
+            match node {
+                Node::Job(_) => writeln!(mermaid, "  {}[{}]", ids[node], name).unwrap(),
+                Node::Resource(_) => writeln!(mermaid, "  {}([{}])", ids[node], name).unwrap(),
+            }
+        }
+        for edge in self.edges.iter() {
+            let arrow = if edge.kind != EdgeKind::Put && !edge.trigger {
+                "-.->"
+            } else {
+                "-->"
+            };
+            match edge.kind {
+                EdgeKind::Passed(ref resource) => writeln!(
+                    mermaid,
+                    "  {} {}|{}| {}",
+                    ids[&edge.from],
+                    arrow,
+                    label(resource),
+                    ids[&edge.to]
+                ),
+                _ => writeln!(mermaid, "  {} {} {}", ids[&edge.from], arrow, ids[&edge.to]),
+            }
+            .unwrap();
+        }
+        mermaid
+    }
+}
@@ -1,4 +1,6 @@
+use crate::modifier::Hooks;
 use crate::schema::Identifier;
+use crate::schema::Number;
 use crate::step::InParallel;
 use crate::step::Step;
 use crate::step::Try;
@@ -10,26 +12,63 @@ pub(crate) enum JobKind {
     Initialized,
 }
 
+// https://concourse-ci.org/jobs.html#schema.build_log_retention_policy
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BuildLogRetention {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) builds: Option<Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) days: Option<Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) minimum_succeeded_builds: Option<Number>,
+}
+
+impl BuildLogRetention {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_builds(mut self, builds: Number) -> Self {
+        self.builds = Some(builds);
+        self
+    }
+
+    pub fn with_days(mut self, days: Number) -> Self {
+        self.days = Some(days);
+        self
+    }
+
+    pub fn with_minimum_succeeded_builds(mut self, minimum_succeeded_builds: Number) -> Self {
+        self.minimum_succeeded_builds = Some(minimum_succeeded_builds);
+        self
+    }
+}
+
+// https://concourse-ci.org/jobs.html
 #[derive(Debug, Clone, Serialize)]
 pub struct Job {
     #[serde(skip_serializing)]
     pub(crate) kind: JobKind,
     pub(crate) name: Identifier,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) old_name: Option<Identifier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     public: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) serial: Option<bool>,
-    pub(crate) plan: Vec<Step>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(crate) on_failure: Option<Step>,
+    pub(crate) serial_groups: Option<Vec<Identifier>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(crate) on_error: Option<Step>,
+    pub(crate) max_in_flight: Option<Number>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(crate) on_abort: Option<Step>,
+    pub(crate) build_log_retention: Option<BuildLogRetention>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(crate) on_success: Option<Step>,
+    pub(crate) disable_manual_trigger: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub(crate) ensure: Option<Step>,
+    pub(crate) interruptible: Option<bool>,
+    pub(crate) plan: Vec<Step>,
+    #[serde(flatten)]
+    pub(crate) hooks: Hooks,
 }
 
 impl Job {
@@ -43,14 +82,16 @@ impl Job {
         Self {
             kind: JobKind::Initialized,
             name: name.to_string(),
+            old_name: None,
             public: None,
             serial: None,
+            serial_groups: None,
+            max_in_flight: None,
+            build_log_retention: None,
+            disable_manual_trigger: None,
+            interruptible: None,
             plan: vec![],
-            on_failure: None,
-            on_error: None,
-            on_abort: None,
-            on_success: None,
-            ensure: None,
+            hooks: Hooks::default(),
         }
     }
 
@@ -77,6 +118,37 @@ impl Job {
         self
     }
 
+    pub fn with_serial_groups(mut self, serial_groups: &[&str]) -> Self {
+        self.serial_groups = Some(serial_groups.iter().map(|g| g.to_string()).collect());
+        self
+    }
+
+    pub fn with_max_in_flight(mut self, max_in_flight: Number) -> Self {
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+
+    pub fn with_build_log_retention(mut self, build_log_retention: BuildLogRetention) -> Self {
+        self.build_log_retention = Some(build_log_retention);
+        self
+    }
+
+    pub fn with_disable_manual_trigger(mut self, disable_manual_trigger: bool) -> Self {
+        self.disable_manual_trigger = Some(disable_manual_trigger);
+        self
+    }
+
+    pub fn with_interruptible(mut self, interruptible: bool) -> Self {
+        self.interruptible = Some(interruptible);
+        self
+    }
+
+    // Renames the job from 'old_name' while preserving its build history.
+    pub fn with_old_name(mut self, old_name: &str) -> Self {
+        self.old_name = Some(old_name.to_string());
+        self
+    }
+
     pub fn then(mut self, step: Step) -> Self {
         self.plan.push(step);
         self
@@ -89,29 +161,49 @@ impl Job {
         self
     }
 
+    pub fn do_(mut self, steps: &[Step]) -> Self {
+        self.plan.push(Step::do_(steps));
+        self
+    }
+
     pub fn parallel(mut self, steps: &[Step]) -> Self {
         self.plan
             .push(Step::InParallel(InParallel::Steps(steps.to_vec())));
         self
     }
 
+    pub fn parallel_with(mut self, limit: Option<Number>, fail_fast: bool, steps: &[Step]) -> Self {
+        if let Some(limit) = limit {
+            if limit < 1 {
+                panic!("The limit of in_parallel steps must be a positive number.");
+            }
+        }
+        self.plan
+            .push(Step::InParallel(InParallel::InParallelConfig {
+                steps: steps.to_vec(),
+                limit,
+                fail_fast,
+            }));
+        self
+    }
+
     pub fn on_failure(mut self, step: Step) -> Self {
-        self.on_failure = Some(step);
+        self.hooks.on_failure = Some(Box::new(step));
         self
     }
 
     pub fn on_error(mut self, step: Step) -> Self {
-        self.on_error = Some(step);
+        self.hooks.on_error = Some(Box::new(step));
         self
     }
 
     pub fn on_abort(mut self, step: Step) -> Self {
-        self.on_abort = Some(step);
+        self.hooks.on_abort = Some(Box::new(step));
         self
     }
 
     pub fn on_success(mut self, step: Step) -> Self {
-        self.on_success = Some(step);
+        self.hooks.on_success = Some(Box::new(step));
         self
     }
 
@@ -126,14 +218,14 @@ impl Job {
                 "One of on_failure, on_error, on_abort hooks must be specified in fallible jobs."
             );
         }
-        self.on_failure = on_failure;
-        self.on_error = on_error;
-        self.on_abort = on_abort;
+        self.hooks.on_failure = on_failure.map(Box::new);
+        self.hooks.on_error = on_error.map(Box::new);
+        self.hooks.on_abort = on_abort.map(Box::new);
         self
     }
 
     pub fn ensure(mut self, ensure: Step) -> Self {
-        self.ensure = Some(ensure);
+        self.hooks.ensure = Some(Box::new(ensure));
         self
     }
 
@@ -141,6 +233,11 @@ impl Job {
         &self.plan
     }
 
+    // Returns steps in the plan followed by the job hooks.
+    pub(crate) fn steps(&self) -> Vec<&Step> {
+        self.plan.iter().chain(self.hooks.steps()).collect()
+    }
+
     pub fn bind(self, var: &mut Self) -> Self {
         *var = self.clone();
         self
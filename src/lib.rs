@@ -1,13 +1,21 @@
 pub mod cook;
 pub mod errors;
 pub mod get;
+pub mod graph;
 pub mod job;
+pub mod load_var;
+pub mod modifier;
+pub mod parse;
 pub mod pipeline;
 pub mod put;
 pub mod resource;
 pub mod schema;
+pub mod set_pipeline;
+pub mod source;
 pub mod step;
 pub mod task;
+pub mod validate;
+pub mod var_source;
 
 #[cfg(test)]
 mod examples;
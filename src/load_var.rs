@@ -0,0 +1,77 @@
+use crate::schema::FilePath;
+use crate::schema::Identifier;
+use crate::schema::Var;
+use crate::step::Step;
+use serde::ser::SerializeStruct;
+use serde::Serialize;
+use serde::Serializer;
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoadVarFormat {
+    Json,
+    Yaml,
+    Trim,
+    Raw,
+}
+
+// https://concourse-ci.org/load-var-step.html
+#[derive(Debug, Clone)]
+pub struct LoadVar {
+    pub(crate) load_var: Identifier,
+    pub(crate) file: FilePath,
+    pub(crate) format: Option<LoadVarFormat>,
+    pub(crate) reveal: bool,
+}
+
+impl Serialize for LoadVar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("LoadVarStep", 4)?;
+        state.serialize_field("load_var", &self.load_var)?;
+        state.serialize_field("file", &self.file)?;
+
+        if let Some(ref format) = self.format {
+            state.serialize_field("format", format)?;
+        }
+
+        if self.reveal {
+            state.serialize_field("reveal", &self.reveal)?;
+        }
+
+        state.end()
+    }
+}
+
+impl LoadVar {
+    pub fn new(name: &str, file: &str) -> Self {
+        Self {
+            load_var: name.to_string(),
+            file: file.to_string(),
+            format: None,
+            reveal: false,
+        }
+    }
+
+    pub fn with_format(mut self, format: LoadVarFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    pub fn with_reveal(mut self, reveal: bool) -> Self {
+        self.reveal = reveal;
+        self
+    }
+
+    pub fn name(&self) -> Identifier {
+        self.load_var.clone()
+    }
+
+    // Returns the handle of the loaded var, which renders as '((.:name))'.
+    pub fn var(&self) -> Var {
+        Var::local(self.load_var.as_str())
+    }
+
+    pub fn to_step(self) -> Step {
+        Step::LoadVar(self)
+    }
+}
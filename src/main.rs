@@ -17,7 +17,7 @@ fn main() -> Result<(), Box<dyn Error>> {
                 Task::new()
                     .with_name("hello-world")
                     .with_inputs(&[&some_git_repo.as_task_input_resource()])
-                    .run(&Command::new("echo", &vec!["hello, world"]))
+                    .run(&Command::new("echo", &["hello, world"]))
                     .to_step(),
             ),
         );
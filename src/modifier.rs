@@ -0,0 +1,301 @@
+use crate::schema::boolean_is_false;
+use crate::schema::Duration;
+use crate::schema::Identifier;
+use crate::schema::Number;
+use crate::schema::Var;
+use crate::step::Step;
+use serde::ser::Error;
+use serde::ser::SerializeMap;
+use serde::ser::SerializeStruct;
+use serde::Serialize;
+use serde::Serializer;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MaxInFlight {
+    All,
+    Limit(Number),
+}
+
+impl Serialize for MaxInFlight {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::All => serializer.serialize_str("all"),
+            Self::Limit(limit) => serializer.serialize_i64(*limit),
+        }
+    }
+}
+
+// https://concourse-ci.org/across-step.html
+#[derive(Debug, Clone, Serialize)]
+pub struct AcrossVar {
+    pub(crate) var: Identifier,
+    pub(crate) values: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max_in_flight: Option<MaxInFlight>,
+}
+
+impl AcrossVar {
+    pub fn new(name: &str, values: &[&str]) -> Self {
+        Self {
+            var: name.to_string(),
+            values: values.iter().map(|v| v.to_string()).collect(),
+            max_in_flight: None,
+        }
+    }
+
+    pub fn with_max_in_flight(mut self, max_in_flight: MaxInFlight) -> Self {
+        if let MaxInFlight::Limit(limit) = max_in_flight {
+            if limit < 1 {
+                panic!(
+                    "The max_in_flight of across var '{}' must be a positive number.",
+                    self.var
+                );
+            }
+        }
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+
+    pub fn name(&self) -> Identifier {
+        self.var.clone()
+    }
+
+    // Returns the handle of the across var, which renders as '((.:name))'.
+    pub fn var(&self) -> Var {
+        Var::local(self.var.as_str())
+    }
+}
+
+// https://concourse-ci.org/jobs.html#schema.job.on_success
+// https://concourse-ci.org/modifier-and-hook-steps.html
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Hooks {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) on_failure: Option<Box<Step>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) on_error: Option<Box<Step>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) on_abort: Option<Box<Step>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) on_success: Option<Box<Step>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) ensure: Option<Box<Step>>,
+}
+
+impl Hooks {
+    pub(crate) fn named(&self) -> [(&'static str, &Option<Box<Step>>); 5] {
+        [
+            ("on_failure", &self.on_failure),
+            ("on_error", &self.on_error),
+            ("on_abort", &self.on_abort),
+            ("on_success", &self.on_success),
+            ("ensure", &self.ensure),
+        ]
+    }
+
+    pub fn steps(&self) -> Vec<&Step> {
+        self.named()
+            .into_iter()
+            .filter_map(|(_, hook)| hook.as_deref())
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps().is_empty()
+    }
+
+    // Serializes hooks as fields of the enclosing step.
+    pub(crate) fn serialize_fields<S: SerializeStruct>(
+        &self,
+        state: &mut S,
+    ) -> Result<(), S::Error> {
+        for (key, hook) in self.named() {
+            if let Some(step) = hook.as_ref() {
+                state.serialize_field(key, step.as_ref())?;
+            }
+        }
+        Ok(())
+    }
+
+    // Returns hooks whose steps are replaced by the result of the mapper.
+    pub(crate) fn try_map<E, F: FnMut(&Step) -> Result<Step, E>>(
+        &self,
+        mut mapper: F,
+    ) -> Result<Hooks, E> {
+        let mut map = |hook: &Option<Box<Step>>| -> Result<Option<Box<Step>>, E> {
+            match hook.as_ref() {
+                Some(step) => Ok(Some(Box::new(mapper(step.as_ref())?))),
+                None => Ok(None),
+            }
+        };
+        Ok(Hooks {
+            on_failure: map(&self.on_failure)?,
+            on_error: map(&self.on_error)?,
+            on_abort: map(&self.on_abort)?,
+            on_success: map(&self.on_success)?,
+            ensure: map(&self.ensure)?,
+        })
+    }
+}
+
+// Modifiers that can be applied to any step.
+// https://concourse-ci.org/modifier-and-hook-steps.html
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Modifiers {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) across: Vec<AcrossVar>,
+    #[serde(skip_serializing_if = "boolean_is_false")]
+    pub(crate) fail_fast: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) timeout: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) attempts: Option<Number>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) tags: Vec<String>,
+    #[serde(flatten)]
+    pub(crate) hooks: Hooks,
+}
+
+impl Modifiers {
+    pub(crate) fn without_hooks(&self) -> Self {
+        Self {
+            hooks: Hooks::default(),
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ModifiedStep {
+    pub(crate) step: Box<Step>,
+    pub(crate) modifiers: Modifiers,
+}
+
+impl Serialize for ModifiedStep {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Modifiers are serialized next to the fields of the modified step.
+        // We cannot use #[serde(flatten)] here, since steps are recursive and
+        // flattening them wraps the serializer type endlessly.
+        let mut fields = vec![];
+        for value in [
+            serde_yaml::to_value(self.step.as_ref()),
+            serde_yaml::to_value(&self.modifiers),
+        ] {
+            match value.map_err(S::Error::custom)? {
+                serde_yaml::Value::Mapping(mapping) => fields.extend(mapping),
+                _ => return Err(S::Error::custom("Only steps can be modified")),
+            }
+        }
+
+        let mut state = serializer.serialize_map(Some(fields.len()))?;
+        for (key, value) in fields.iter() {
+            state.serialize_entry(key, value)?;
+        }
+        state.end()
+    }
+}
+
+impl ModifiedStep {
+    pub(crate) fn with_step(&self, step: Step) -> Self {
+        Self {
+            step: Box::new(step),
+            modifiers: self.modifiers.clone(),
+        }
+    }
+
+    pub(crate) fn with_hooks(&self, hooks: Hooks) -> Self {
+        let mut this = self.clone();
+        this.modifiers.hooks = hooks;
+        this
+    }
+
+    pub fn step(&self) -> &Step {
+        self.step.as_ref()
+    }
+
+    pub fn modifiers(&self) -> &Modifiers {
+        &self.modifiers
+    }
+}
+
+impl Step {
+    fn modify<F: FnOnce(&mut Modifiers)>(self, modifier: F) -> Step {
+        let mut modified = match self {
+            Step::Modified(modified) => modified,
+            step => ModifiedStep {
+                step: Box::new(step),
+                modifiers: Modifiers::default(),
+            },
+        };
+        modifier(&mut modified.modifiers);
+        Step::Modified(modified)
+    }
+
+    pub fn across(self, vars: &[&AcrossVar]) -> Step {
+        if vars.is_empty() {
+            panic!("across() requires at least one var.");
+        }
+        self.modify(|modifiers| {
+            modifiers
+                .across
+                .extend(vars.iter().map(|var| (*var).clone()))
+        })
+    }
+
+    pub fn with_fail_fast(self, fail_fast: bool) -> Step {
+        self.modify(|modifiers| modifiers.fail_fast = fail_fast)
+    }
+
+    pub fn with_timeout(self, timeout: Duration) -> Step {
+        self.modify(|modifiers| modifiers.timeout = Some(timeout))
+    }
+
+    pub fn with_attempts(self, attempts: Number) -> Step {
+        if attempts < 1 {
+            panic!("The attempts of a step must be a positive number.");
+        }
+        self.modify(|modifiers| modifiers.attempts = Some(attempts))
+    }
+
+    pub fn with_tags(self, tags: &[&str]) -> Step {
+        self.modify(|modifiers| modifiers.tags = tags.iter().map(|t| t.to_string()).collect())
+    }
+
+    fn hook<F: FnOnce(&mut Hooks)>(self, hook: F) -> Step {
+        // Tasks have their own hooks, attach hooks to them to avoid emitting
+        // the same hook twice.
+        match self {
+            Step::Task(mut task) => {
+                hook(&mut task.hooks);
+                Step::Task(task)
+            }
+            Step::Modified(mut modified) if matches!(*modified.step, Step::Task(_)) => {
+                if let Step::Task(ref mut task) = *modified.step {
+                    hook(&mut task.hooks);
+                }
+                Step::Modified(modified)
+            }
+            step => step.modify(|modifiers| hook(&mut modifiers.hooks)),
+        }
+    }
+
+    pub fn on_success(self, step: Step) -> Step {
+        self.hook(|hooks| hooks.on_success = Some(Box::new(step)))
+    }
+
+    pub fn on_failure(self, step: Step) -> Step {
+        self.hook(|hooks| hooks.on_failure = Some(Box::new(step)))
+    }
+
+    pub fn on_abort(self, step: Step) -> Step {
+        self.hook(|hooks| hooks.on_abort = Some(Box::new(step)))
+    }
+
+    pub fn on_error(self, step: Step) -> Step {
+        self.hook(|hooks| hooks.on_error = Some(Box::new(step)))
+    }
+
+    pub fn ensure(self, step: Step) -> Step {
+        self.hook(|hooks| hooks.ensure = Some(Box::new(step)))
+    }
+}
@@ -0,0 +1,1190 @@
+use crate::errors::Errors;
+use crate::get::Get;
+use crate::job::BuildLogRetention;
+use crate::job::Job;
+use crate::load_var::LoadVar;
+use crate::load_var::LoadVarFormat;
+use crate::modifier::AcrossVar;
+use crate::modifier::Hooks;
+use crate::modifier::MaxInFlight;
+use crate::modifier::ModifiedStep;
+use crate::modifier::Modifiers;
+use crate::pipeline::Group;
+use crate::pipeline::Pipeline;
+use crate::put::Put;
+use crate::put::PutInputs;
+use crate::resource::CheckEvery;
+use crate::resource::Resource;
+use crate::resource::ResourceTypes;
+use crate::schema::ByteSize;
+use crate::schema::Config;
+use crate::schema::Duration;
+use crate::schema::Identifier;
+use crate::schema::Number;
+use crate::schema::Value;
+use crate::schema::Version;
+use crate::set_pipeline::SetPipeline;
+use crate::source::Source;
+use crate::step::InParallel;
+use crate::step::Step;
+use crate::task::Command;
+use crate::task::ContainerLimits;
+use crate::task::Input;
+use crate::task::Output;
+use crate::task::Platform;
+use crate::task::Task;
+use crate::task::TaskConfig;
+use crate::task::TaskDef;
+use crate::var_source::AwsConfig;
+use crate::var_source::DummyConfig;
+use crate::var_source::VarSource;
+use crate::var_source::VaultConfig;
+use serde_yaml::Mapping;
+use serde_yaml::Value as Yaml;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+const HOOKS: [&str; 5] = ["on_failure", "on_error", "on_abort", "on_success", "ensure"];
+
+const STEPS: [&str; 8] = [
+    "get",
+    "put",
+    "task",
+    "in_parallel",
+    "try",
+    "set_pipeline",
+    "load_var",
+    "do",
+];
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+// Versions, across values, etc. are strings, but YAML reads '1.0' as a number.
+// So does Go's zero duration '0'.
+fn scalar(value: &Yaml) -> Option<String> {
+    match value {
+        Yaml::String(s) => Some(s.clone()),
+        Yaml::Number(n) => Some(n.to_string()),
+        Yaml::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn builtin_type(name: &str) -> Option<ResourceTypes> {
+    match name {
+        "docker-image" => Some(ResourceTypes::DockerImage),
+        "git" => Some(ResourceTypes::Git),
+        "registry-image" => Some(ResourceTypes::RegistryImage),
+        "time" => Some(ResourceTypes::Time),
+        _ => None,
+    }
+}
+
+// Fields of a mapping. Fields that were never taken are reported as unknown
+// once the mapping is parsed.
+struct Fields<'a> {
+    path: String,
+    mapping: &'a Mapping,
+    taken: BTreeSet<String>,
+}
+
+impl<'a> Fields<'a> {
+    fn take(&mut self, key: &str) -> Option<&'a Yaml> {
+        self.taken.insert(key.to_string());
+        self.mapping.get(key).filter(|value| !value.is_null())
+    }
+
+    fn has(&self, key: &str) -> bool {
+        self.mapping.contains_key(key)
+    }
+
+    fn path(&self, key: &str) -> String {
+        join(self.path.as_str(), key)
+    }
+}
+
+// Collects every problem rather than stopping at the first one, like
+// validate::validate does.
+#[derive(Default)]
+struct Parser {
+    resource_types: BTreeMap<Identifier, ResourceTypes>,
+    resources: BTreeMap<Identifier, Resource>,
+    problems: Vec<String>,
+}
+
+impl Parser {
+    fn problem(&mut self, path: &str, message: &str) {
+        if path.is_empty() {
+            self.problems.push(message.to_string());
+        } else {
+            self.problems.push(format!("{}: {}", path, message));
+        }
+    }
+
+    fn fields<'a>(&mut self, path: &str, value: &'a Yaml) -> Option<Fields<'a>> {
+        match value {
+            Yaml::Mapping(ref mapping) => Some(Fields {
+                path: path.to_string(),
+                mapping,
+                taken: BTreeSet::new(),
+            }),
+            _ => {
+                self.problem(path, "Expected a mapping");
+                None
+            }
+        }
+    }
+
+    fn done(&mut self, fields: Fields) {
+        for key in fields.mapping.keys() {
+            match key.as_str() {
+                Some(key) if fields.taken.contains(key) => { /* Do nothing. */ }
+                Some(key) => self.problem(&fields.path, &format!("Unknown field '{}'", key)),
+                None => self.problem(&fields.path, "Field names must be strings"),
+            }
+        }
+    }
+
+    fn field<T, F: FnOnce(&Yaml) -> Option<T>>(
+        &mut self,
+        fields: &mut Fields,
+        key: &str,
+        expected: &str,
+        convert: F,
+    ) -> Option<T> {
+        let value = fields.take(key)?;
+        let converted = convert(value);
+        if converted.is_none() {
+            self.problem(&fields.path(key), &format!("Expected {}", expected));
+        }
+        converted
+    }
+
+    fn required<T>(&mut self, fields: &Fields, key: &str, value: Option<T>) -> Option<T> {
+        if value.is_none() && !fields.has(key) {
+            self.problem(&fields.path, &format!("Missing field '{}'", key));
+        }
+        value
+    }
+
+    fn string(&mut self, fields: &mut Fields, key: &str) -> Option<String> {
+        self.field(fields, key, "a string", |value| {
+            value.as_str().map(|s| s.to_string())
+        })
+    }
+
+    fn required_string(&mut self, fields: &mut Fields, key: &str) -> Option<String> {
+        let value = self.string(fields, key);
+        self.required(fields, key, value)
+    }
+
+    fn boolean(&mut self, fields: &mut Fields, key: &str) -> Option<bool> {
+        self.field(fields, key, "a boolean", |value| value.as_bool())
+    }
+
+    fn number(&mut self, fields: &mut Fields, key: &str) -> Option<Number> {
+        self.field(fields, key, "a number", |value| value.as_i64())
+    }
+
+    fn duration(&mut self, fields: &mut Fields, key: &str) -> Option<Duration> {
+        self.field(fields, key, "a duration, e.g., '1h30m'", |value| {
+            scalar(value).as_deref().and_then(Duration::parse)
+        })
+    }
+
+    fn strings(&mut self, fields: &mut Fields, key: &str) -> Option<Vec<String>> {
+        self.field(fields, key, "a list of strings", |value| {
+            value
+                .as_sequence()?
+                .iter()
+                .map(|item| item.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+    }
+
+    fn string_map(&mut self, fields: &mut Fields, key: &str) -> Option<BTreeMap<String, String>> {
+        self.field(fields, key, "a mapping of strings", |value| {
+            value
+                .as_mapping()?
+                .iter()
+                .map(|(k, v)| Some((k.as_str()?.to_string(), scalar(v)?)))
+                .collect()
+        })
+    }
+
+    fn config(&mut self, fields: &mut Fields, key: &str) -> Option<Config> {
+        self.field(fields, key, "a mapping", |value| {
+            match Value::from_yaml(value.clone()) {
+                Some(Value::Map(config)) => Some(config),
+                _ => None,
+            }
+        })
+    }
+
+    fn list<'a>(&mut self, fields: &mut Fields<'a>, key: &str) -> Vec<(String, &'a Yaml)> {
+        let path = fields.path(key);
+        match fields.take(key) {
+            Some(Yaml::Sequence(ref items)) => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| (format!("{}[{}]", path, i), item))
+                .collect(),
+            Some(_) => {
+                self.problem(&path, "Expected a list");
+                vec![]
+            }
+            None => vec![],
+        }
+    }
+
+    // Items of named lists, e.g., jobs, are shown by name rather than index.
+    fn named_list<'a>(&mut self, fields: &mut Fields<'a>, key: &str) -> Vec<(String, &'a Yaml)> {
+        self.list(fields, key)
+            .into_iter()
+            .map(
+                |(path, item)| match item.get("name").and_then(|name| name.as_str()) {
+                    Some(name) => (format!("{}[{}]", key, name), item),
+                    None => (path, item),
+                },
+            )
+            .collect()
+    }
+
+    fn version(&mut self, fields: &mut Fields, key: &str) -> Option<Version> {
+        self.field(
+            fields,
+            key,
+            "'latest', 'every' or a mapping",
+            |value| match value {
+                Yaml::String(s) if s == "latest" => Some(Version::Latest),
+                Yaml::String(s) if s == "every" => Some(Version::Every),
+                Yaml::Mapping(ref mapping) => mapping
+                    .iter()
+                    .map(|(k, v)| Some((k.as_str()?.to_string(), scalar(v)?)))
+                    .collect::<Option<BTreeMap<_, _>>>()
+                    .map(Version::Custom),
+                _ => None,
+            },
+        )
+    }
+
+    fn check_every(&mut self, fields: &mut Fields, key: &str) -> Option<CheckEvery> {
+        self.field(
+            fields,
+            key,
+            "'never' or a duration",
+            |value| match scalar(value)?.as_str() {
+                "never" => Some(CheckEvery::Never),
+                interval => Duration::parse(interval).map(CheckEvery::Interval),
+            },
+        )
+    }
+
+    fn lookup_type(&mut self, path: &str, name: &str) -> Option<ResourceTypes> {
+        let resource_type = self
+            .resource_types
+            .get(name)
+            .cloned()
+            .or_else(|| builtin_type(name));
+        if resource_type.is_none() {
+            self.problem(
+                path,
+                &format!("Resource type '{}' is not declared in the pipeline", name),
+            );
+        }
+        resource_type
+    }
+
+    fn lookup_resource(&mut self, path: &str, name: &str) -> Option<Resource> {
+        let resource = self.resources.get(name).cloned();
+        if resource.is_none() {
+            self.problem(
+                path,
+                &format!("Resource '{}' is not declared in the pipeline", name),
+            );
+        }
+        resource
+    }
+
+    // Custom types may be based on types that are declared after them, so
+    // they are resolved on demand.
+    fn resource_types(&mut self, declared: &[(String, &Yaml)]) {
+        let by_name = declared
+            .iter()
+            .filter_map(|(path, value)| {
+                let name = value.get("name")?.as_str()?;
+                Some((name.to_string(), (path.clone(), *value)))
+            })
+            .collect::<BTreeMap<_, _>>();
+        for (path, value) in declared.iter() {
+            match value.get("name").and_then(|name| name.as_str()) {
+                Some(name) if self.resource_types.contains_key(name) => { /* Do nothing. */ }
+                _ => {
+                    self.resource_type(path, value, &by_name, &mut vec![]);
+                }
+            }
+        }
+    }
+
+    fn resource_type(
+        &mut self,
+        path: &str,
+        value: &Yaml,
+        declared: &BTreeMap<String, (String, &Yaml)>,
+        resolving: &mut Vec<String>,
+    ) -> Option<ResourceTypes> {
+        let mut fields = self.fields(path, value)?;
+        let name = self.required_string(&mut fields, "name");
+        let type_name = self.required_string(&mut fields, "type");
+        let source = self.config(&mut fields, "source");
+        let params = self.config(&mut fields, "params");
+        let privileged = self.boolean(&mut fields, "privileged");
+        let check_every = self.check_every(&mut fields, "check_every");
+        let tags = self.strings(&mut fields, "tags");
+        let defaults = self.config(&mut fields, "defaults");
+        self.done(fields);
+
+        let name = name?;
+        let type_name = type_name?;
+        resolving.push(name.clone());
+        let type_ = match declared.get(&type_name) {
+            _ if self.resource_types.contains_key(&type_name) => {
+                self.resource_types.get(&type_name).cloned()
+            }
+            Some(_) if resolving.contains(&type_name) => {
+                self.problem(
+                    &join(path, "type"),
+                    &format!("Resource type '{}' is based on itself", type_name),
+                );
+                None
+            }
+            Some((base_path, base)) => self.resource_type(base_path, base, declared, resolving),
+            None => self.lookup_type(&join(path, "type"), &type_name),
+        };
+        resolving.pop();
+
+        let mut resource_type = ResourceTypes::new(&name, type_?);
+        if let ResourceTypes::Custom {
+            source: ref mut type_source,
+            params: ref mut type_params,
+            privileged: ref mut type_privileged,
+            check_every: ref mut type_check_every,
+            tags: ref mut type_tags,
+            defaults: ref mut type_defaults,
+            ..
+        } = resource_type
+        {
+            *type_source = source.unwrap_or_default();
+            *type_params = params.unwrap_or_default();
+            *type_privileged = privileged.unwrap_or(false);
+            *type_check_every = check_every;
+            *type_tags = tags.unwrap_or_default();
+            *type_defaults = defaults.unwrap_or_default();
+        }
+        self.resource_types.insert(name, resource_type.clone());
+        Some(resource_type)
+    }
+
+    fn resource(&mut self, path: &str, value: &Yaml) -> Option<Resource> {
+        let mut fields = self.fields(path, value)?;
+        let name = self.required_string(&mut fields, "name");
+        let type_name = self.required_string(&mut fields, "type");
+        let icon = self.string(&mut fields, "icon");
+        let source = self.config(&mut fields, "source");
+        let version = self.version(&mut fields, "version");
+        let check_every = self.check_every(&mut fields, "check_every");
+        let check_timeout = self.duration(&mut fields, "check_timeout");
+        let tags = self.strings(&mut fields, "tags");
+        let public = self.boolean(&mut fields, "public");
+        let webhook_token = self.string(&mut fields, "webhook_token");
+        let expose_build_created_by = self.boolean(&mut fields, "expose_build_created_by");
+        self.done(fields);
+
+        let type_ = self.lookup_type(&join(path, "type"), type_name?.as_str())?;
+        let mut resource = Resource::new(name?.as_str(), &type_);
+        resource.icon = icon;
+        resource.source = Source::raw(source.unwrap_or_default());
+        resource.version = version;
+        resource.check_every = check_every;
+        resource.check_timeout = check_timeout;
+        resource.tags = tags.unwrap_or_default();
+        resource.public = public.unwrap_or(false);
+        resource.webhook_token = webhook_token;
+        resource.expose_build_created_by = expose_build_created_by.unwrap_or(false);
+        Some(resource)
+    }
+
+    fn hooks(&mut self, fields: &mut Fields) -> Hooks {
+        let mut hooks = Hooks::default();
+        for name in HOOKS {
+            let path = fields.path(name);
+            let step = match fields.take(name) {
+                Some(value) => self.step(&path, value).map(Box::new),
+                None => continue,
+            };
+            match name {
+                "on_failure" => hooks.on_failure = step,
+                "on_error" => hooks.on_error = step,
+                "on_abort" => hooks.on_abort = step,
+                "on_success" => hooks.on_success = step,
+                _ => hooks.ensure = step,
+            }
+        }
+        hooks
+    }
+
+    fn job(&mut self, path: &str, value: &Yaml) -> Option<Job> {
+        let mut fields = self.fields(path, value)?;
+        let name = self.required_string(&mut fields, "name");
+        let old_name = self.string(&mut fields, "old_name");
+        let public = self.boolean(&mut fields, "public");
+        let serial = self.boolean(&mut fields, "serial");
+        let serial_groups = self.strings(&mut fields, "serial_groups");
+        let max_in_flight = self.number(&mut fields, "max_in_flight");
+        let build_log_retention = fields
+            .take("build_log_retention")
+            .and_then(|value| self.build_log_retention(&join(path, "build_log_retention"), value));
+        let disable_manual_trigger = self.boolean(&mut fields, "disable_manual_trigger");
+        let interruptible = self.boolean(&mut fields, "interruptible");
+        let plan = self
+            .list(&mut fields, "plan")
+            .into_iter()
+            .filter_map(|(step_path, step)| self.step(&step_path, step))
+            .collect();
+        let hooks = self.hooks(&mut fields);
+        self.done(fields);
+
+        let mut job = Job::new(name?.as_str());
+        if let Some(public) = public {
+            job = job.with_public(public);
+        }
+        job.old_name = old_name;
+        job.serial = serial;
+        job.serial_groups = serial_groups;
+        job.max_in_flight = max_in_flight;
+        job.build_log_retention = build_log_retention;
+        job.disable_manual_trigger = disable_manual_trigger;
+        job.interruptible = interruptible;
+        job.plan = plan;
+        job.hooks = hooks;
+        Some(job)
+    }
+
+    fn build_log_retention(&mut self, path: &str, value: &Yaml) -> Option<BuildLogRetention> {
+        let mut fields = self.fields(path, value)?;
+        let retention = BuildLogRetention {
+            builds: self.number(&mut fields, "builds"),
+            days: self.number(&mut fields, "days"),
+            minimum_succeeded_builds: self.number(&mut fields, "minimum_succeeded_builds"),
+        };
+        self.done(fields);
+        Some(retention)
+    }
+
+    fn step(&mut self, path: &str, value: &Yaml) -> Option<Step> {
+        let mut fields = self.fields(path, value)?;
+        let kinds = STEPS
+            .into_iter()
+            .filter(|kind| fields.has(kind))
+            .collect::<Vec<_>>();
+        let kind = match kinds[..] {
+            [kind] => kind,
+            [] => {
+                self.problem(
+                    path,
+                    &format!("Expected a step, i.e., one of '{}'", STEPS.join("', '")),
+                );
+                return None;
+            }
+            _ => {
+                self.problem(
+                    path,
+                    &format!("Only one of '{}' can be set", kinds.join("', '")),
+                );
+                return None;
+            }
+        };
+        // Paths name the step the same way validate::Diagnostic does.
+        fields.path = match fields.mapping.get(kind).and_then(|name| name.as_str()) {
+            Some(name) if !matches!(kind, "in_parallel" | "try" | "do") => {
+                format!("{}.{}[{}]", path, kind, name)
+            }
+            _ => format!("{}.{}", path, kind),
+        };
+
+        let step = match kind {
+            "get" => self.get_step(&mut fields).map(Step::Get),
+            "put" => self.put_step(&mut fields).map(Step::Put),
+            "task" => self.task_step(&mut fields).map(Step::Task),
+            "in_parallel" => self.in_parallel_step(&mut fields).map(Step::InParallel),
+            "try" => {
+                let path = fields.path.clone();
+                fields
+                    .take("try")
+                    .and_then(|value| self.step(&path, value))
+                    .map(Step::try_)
+            }
+            "set_pipeline" => self.set_pipeline_step(&mut fields).map(Step::SetPipeline),
+            "load_var" => self.load_var_step(&mut fields).map(Step::LoadVar),
+            _ => Some(Step::Do(
+                self.list(&mut fields, "do")
+                    .into_iter()
+                    .filter_map(|(step_path, step)| self.step(&step_path, step))
+                    .collect(),
+            )),
+        };
+
+        let across = self
+            .list(&mut fields, "across")
+            .into_iter()
+            .filter_map(|(var_path, var)| self.across_var(&var_path, var))
+            .collect::<Vec<_>>();
+        let modifiers = Modifiers {
+            across,
+            fail_fast: self.boolean(&mut fields, "fail_fast").unwrap_or(false),
+            timeout: self.duration(&mut fields, "timeout"),
+            attempts: self.number(&mut fields, "attempts"),
+            tags: self.strings(&mut fields, "tags").unwrap_or_default(),
+            hooks: self.hooks(&mut fields),
+        };
+        self.done(fields);
+
+        let mut step = step?;
+        let mut modifiers = modifiers;
+        // Hooks of tasks belong to the task, see Step::hook.
+        if let Step::Task(ref mut task) = step {
+            task.hooks = std::mem::take(&mut modifiers.hooks);
+        }
+        if modifiers.across.is_empty()
+            && !modifiers.fail_fast
+            && modifiers.timeout.is_none()
+            && modifiers.attempts.is_none()
+            && modifiers.tags.is_empty()
+            && modifiers.hooks.is_empty()
+        {
+            Some(step)
+        } else {
+            Some(Step::Modified(ModifiedStep {
+                step: Box::new(step),
+                modifiers,
+            }))
+        }
+    }
+
+    fn across_var(&mut self, path: &str, value: &Yaml) -> Option<AcrossVar> {
+        let mut fields = self.fields(path, value)?;
+        let var = self.required_string(&mut fields, "var");
+        let values = self.field(&mut fields, "values", "a list of values", |value| {
+            value.as_sequence()?.iter().map(scalar).collect()
+        });
+        let values = self.required(&fields, "values", values);
+        let max_in_flight = self.field(
+            &mut fields,
+            "max_in_flight",
+            "'all' or a positive number",
+            |value| match value {
+                Yaml::String(s) if s == "all" => Some(MaxInFlight::All),
+                _ => value
+                    .as_i64()
+                    .filter(|limit| *limit > 0)
+                    .map(MaxInFlight::Limit),
+            },
+        );
+        self.done(fields);
+
+        Some(AcrossVar {
+            var: var?,
+            values: values?,
+            max_in_flight,
+        })
+    }
+
+    fn get_step(&mut self, fields: &mut Fields) -> Option<Get> {
+        let get = self.required_string(fields, "get");
+        let resource = self.string(fields, "resource");
+        let version = self.version(fields, "version");
+        let trigger = self.boolean(fields, "trigger");
+        let passed = self.strings(fields, "passed");
+        let params = self.config(fields, "params");
+
+        let get = get?;
+        let (alias, name) = match resource {
+            Some(resource) => (get, resource),
+            None => (String::new(), get),
+        };
+        let resource = self.lookup_resource(&fields.path, name.as_str())?;
+        let mut get = Get::from(alias.as_str(), &resource, version);
+        get.trigger = trigger.unwrap_or(false);
+        get.passed = passed.unwrap_or_default();
+        get.params = params.unwrap_or_default();
+        Some(get)
+    }
+
+    fn put_step(&mut self, fields: &mut Fields) -> Option<Put> {
+        let put = self.required_string(fields, "put");
+        let resource = self.string(fields, "resource");
+        let params = self.config(fields, "params");
+        let inputs = self.field(
+            fields,
+            "inputs",
+            "'all', 'detect' or a list of names",
+            |value| match value {
+                Yaml::String(s) if s == "all" => Some(PutInputs::All),
+                Yaml::String(s) if s == "detect" => Some(PutInputs::Detect),
+                Yaml::Sequence(ref inputs) => inputs
+                    .iter()
+                    .map(|input| input.as_str().map(|s| s.to_string()))
+                    .collect::<Option<Vec<_>>>()
+                    .map(PutInputs::List),
+                _ => None,
+            },
+        );
+        let get_params = self.config(fields, "get_params");
+        let no_get = self.boolean(fields, "no_get");
+
+        let put = put?;
+        let (alias, name) = match resource {
+            Some(resource) => (put, resource),
+            None => (String::new(), put),
+        };
+        let resource = self.lookup_resource(&fields.path, name.as_str())?;
+        let mut put = Put::from(alias.as_str(), &resource);
+        put.params = params.unwrap_or_default();
+        put.inputs = inputs;
+        put.get_params = get_params.unwrap_or_default();
+        put.no_get = no_get.unwrap_or(false);
+        Some(put)
+    }
+
+    fn task_step(&mut self, fields: &mut Fields) -> Option<Task> {
+        let name = self.required_string(fields, "task");
+        let file = self.string(fields, "file");
+        let image = self.string(fields, "image");
+        let image = match image {
+            Some(image) => Some(self.lookup_resource(&fields.path("image"), image.as_str())?),
+            None => None,
+        };
+        let config_path = fields.path("config");
+        let config = fields
+            .take("config")
+            .map(|config| self.task_config(&config_path, config, image.is_some()));
+        let privileged = self.boolean(fields, "privileged");
+        let container_limits = fields
+            .take("container_limits")
+            .map(|value| self.container_limits(&fields.path("container_limits"), value));
+        let params = self.config(fields, "params");
+        let input_mapping = self.string_map(fields, "input_mapping");
+        let output_mapping = self.string_map(fields, "output_mapping");
+
+        let mut task = match (config, file) {
+            (Some(config), None) => {
+                let mut task = Task::new();
+                task.task_def = TaskDef::Config { config: config? };
+                task
+            }
+            (None, Some(file)) => Task::from_file(file.as_str()),
+            (None, None) => {
+                self.problem(&fields.path, "Missing field 'config' or 'file'");
+                return None;
+            }
+            (Some(_), Some(_)) => {
+                self.problem(&fields.path, "Only one of 'config', 'file' can be set");
+                return None;
+            }
+        };
+        task = task.with_name(name?.as_str());
+        if let Some(image) = image {
+            task = task.with_image(image.as_task_image_resource());
+        }
+        task = task.with_privileged(privileged.unwrap_or(false));
+        if let Some(container_limits) = container_limits {
+            task = task.with_container_limits(container_limits?);
+        }
+        if let Some(params) = params {
+            let params = params
+                .iter()
+                .map(|(k, v)| (k.as_str(), v.clone()))
+                .collect::<Vec<_>>();
+            task = task.with_params(&params);
+        }
+        task.input_mapping = input_mapping;
+        task.output_mapping = output_mapping;
+        Some(task)
+    }
+
+    fn container_limits(&mut self, path: &str, value: &Yaml) -> Option<ContainerLimits> {
+        let mut fields = self.fields(path, value)?;
+        let cpu = self.field(&mut fields, "cpu", "a positive number", |value| {
+            value.as_i64().filter(|cpu| *cpu > 0)
+        });
+        let memory = self.field(
+            &mut fields,
+            "memory",
+            "a size, e.g., '512MB'",
+            |value| match value {
+                Yaml::Number(ref n) => n.as_u64().map(ByteSize::bytes),
+                Yaml::String(ref s) => ByteSize::parse(s),
+                _ => None,
+            },
+        );
+        self.done(fields);
+
+        let mut container_limits = ContainerLimits::new();
+        if let Some(cpu) = cpu {
+            container_limits = container_limits.with_cpu(cpu);
+        }
+        if let Some(memory) = memory {
+            container_limits = container_limits.with_memory(memory);
+        }
+        Some(container_limits)
+    }
+
+    // The image can be left out when the task sets 'image' instead.
+    fn task_config(&mut self, path: &str, value: &Yaml, has_image: bool) -> Option<TaskConfig> {
+        let mut fields = self.fields(path, value)?;
+        let platform = self.field(
+            &mut fields,
+            "platform",
+            "'linux', 'darwin' or 'windows'",
+            |value| match value.as_str()? {
+                "linux" => Some(Platform::Linux),
+                "darwin" => Some(Platform::Darwin),
+                "windows" => Some(Platform::Windows),
+                _ => None,
+            },
+        );
+        let platform = self.required(&fields, "platform", platform);
+        let image_resource = fields
+            .take("image_resource")
+            .map(|value| self.image_resource(&join(path, "image_resource"), value));
+        let rootfs_uri = self.string(&mut fields, "rootfs_uri");
+        let run = fields
+            .take("run")
+            .map(|value| self.command(&join(path, "run"), value));
+        let run = self.required(&fields, "run", run);
+        let params = self.config(&mut fields, "params");
+        let inputs = self.list(&mut fields, "inputs");
+        let inputs = if fields.has("inputs") {
+            Some(
+                inputs
+                    .into_iter()
+                    .filter_map(|(input_path, input)| self.input(&input_path, input))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        let outputs = self.list(&mut fields, "outputs");
+        let outputs = if fields.has("outputs") {
+            Some(
+                outputs
+                    .into_iter()
+                    .filter_map(|(output_path, output)| self.output(&output_path, output))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        let caches = self
+            .list(&mut fields, "caches")
+            .into_iter()
+            .filter_map(|(cache_path, cache)| {
+                let mut fields = self.fields(&cache_path, cache)?;
+                let path = self.required_string(&mut fields, "path");
+                self.done(fields);
+                path
+            })
+            .collect::<Vec<_>>();
+        let container_limits = fields
+            .take("container_limits")
+            .map(|value| self.container_limits(&join(path, "container_limits"), value));
+        let has_image_resource = fields.has("image_resource");
+        let has_rootfs_uri = fields.has("rootfs_uri");
+        self.done(fields);
+
+        let mut config = TaskConfig::linux_default().with_platform(platform?);
+        match (image_resource, rootfs_uri) {
+            (Some(image_resource), None) => {
+                config = config.with_image_resource(&image_resource?.as_task_image_resource());
+            }
+            (None, Some(rootfs_uri)) => config = config.with_rootfs_uri(rootfs_uri.as_str()),
+            (None, None) if !has_image_resource && !has_rootfs_uri && !has_image => {
+                self.problem(path, "Missing field 'image_resource' or 'rootfs_uri'");
+                return None;
+            }
+            (None, None) => { /* Do nothing. */ }
+            (Some(_), Some(_)) => {
+                self.problem(
+                    path,
+                    "Only one of 'image_resource', 'rootfs_uri' can be set",
+                );
+                return None;
+            }
+        }
+        config = config.run(&run??);
+        config.params = params;
+        config.inputs = inputs;
+        config.outputs = outputs;
+        config = config.with_caches(&caches.iter().map(|c| c.as_str()).collect::<Vec<_>>());
+        if let Some(container_limits) = container_limits {
+            config = config.with_container_limits(container_limits?);
+        }
+        Some(config)
+    }
+
+    // Anonymous resources only exist in task configs, so they are kept as
+    // resources without a name.
+    fn image_resource(&mut self, path: &str, value: &Yaml) -> Option<Resource> {
+        let mut fields = self.fields(path, value)?;
+        let type_name = self.required_string(&mut fields, "type");
+        let source = self.config(&mut fields, "source");
+        let version = self.version(&mut fields, "version");
+        self.done(fields);
+
+        let type_ = self.lookup_type(&join(path, "type"), type_name?.as_str())?;
+        let mut resource = Resource::new("", &type_);
+        resource.source = Source::raw(source.unwrap_or_default());
+        resource.version = version;
+        Some(resource)
+    }
+
+    fn command(&mut self, path: &str, value: &Yaml) -> Option<Command> {
+        let mut fields = self.fields(path, value)?;
+        let command_path = self.required_string(&mut fields, "path");
+        let args = self.strings(&mut fields, "args");
+        let dir = self.string(&mut fields, "dir");
+        let user = self.string(&mut fields, "user");
+        self.done(fields);
+
+        let args = args.unwrap_or_default();
+        let mut command = Command::new(
+            command_path?.as_str(),
+            &args.iter().map(|arg| arg.as_str()).collect::<Vec<_>>(),
+        );
+        if let Some(dir) = dir {
+            command = command.with_dir(dir.as_str());
+        }
+        if let Some(user) = user {
+            command = command.with_user(user.as_str());
+        }
+        Some(command)
+    }
+
+    fn input(&mut self, path: &str, value: &Yaml) -> Option<Input> {
+        let mut fields = self.fields(path, value)?;
+        let name = self.required_string(&mut fields, "name");
+        let input_path = self.string(&mut fields, "path");
+        let optional = self.boolean(&mut fields, "optional");
+        self.done(fields);
+
+        let mut input = Input::new(name?.as_str()).with_optional(optional.unwrap_or(false));
+        if let Some(input_path) = input_path {
+            input = input.with_path(input_path.as_str());
+        }
+        Some(input)
+    }
+
+    fn output(&mut self, path: &str, value: &Yaml) -> Option<Output> {
+        let mut fields = self.fields(path, value)?;
+        let name = self.required_string(&mut fields, "name");
+        let output_path = self.string(&mut fields, "path");
+        self.done(fields);
+
+        let mut output = Output::new(name?.as_str());
+        if let Some(output_path) = output_path {
+            output = output.with_path(output_path.as_str());
+        }
+        Some(output)
+    }
+
+    fn in_parallel_step(&mut self, fields: &mut Fields) -> Option<InParallel> {
+        let path = fields.path.clone();
+        match fields.take("in_parallel")? {
+            Yaml::Sequence(ref steps) => Some(InParallel::Steps(
+                steps
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, step)| self.step(&format!("{}[{}]", path, i), step))
+                    .collect(),
+            )),
+            value => {
+                let mut fields = self.fields(&path, value)?;
+                let steps = self
+                    .list(&mut fields, "steps")
+                    .into_iter()
+                    .filter_map(|(step_path, step)| self.step(&step_path, step))
+                    .collect();
+                let limit = self.field(&mut fields, "limit", "a positive number", |value| {
+                    value.as_i64().filter(|limit| *limit > 0)
+                });
+                let fail_fast = self.boolean(&mut fields, "fail_fast");
+                self.done(fields);
+                Some(InParallel::InParallelConfig {
+                    steps,
+                    limit,
+                    fail_fast: fail_fast.unwrap_or(false),
+                })
+            }
+        }
+    }
+
+    fn set_pipeline_step(&mut self, fields: &mut Fields) -> Option<SetPipeline> {
+        let name = self.required_string(fields, "set_pipeline");
+        let file = self.required_string(fields, "file");
+        let instance_vars = self.config(fields, "instance_vars");
+        let vars = self.config(fields, "vars");
+        let var_files = self.strings(fields, "var_files");
+        let team = self.string(fields, "team");
+
+        let mut set_pipeline = SetPipeline::new(name?.as_str(), file?.as_str());
+        set_pipeline.instance_vars = instance_vars;
+        set_pipeline.vars = vars;
+        set_pipeline.var_files = var_files;
+        set_pipeline.team = team;
+        Some(set_pipeline)
+    }
+
+    fn load_var_step(&mut self, fields: &mut Fields) -> Option<LoadVar> {
+        let name = self.required_string(fields, "load_var");
+        let file = self.required_string(fields, "file");
+        let format = self.field(
+            fields,
+            "format",
+            "'json', 'yaml', 'trim' or 'raw'",
+            |value| match value.as_str()? {
+                "json" => Some(LoadVarFormat::Json),
+                "yaml" => Some(LoadVarFormat::Yaml),
+                "trim" => Some(LoadVarFormat::Trim),
+                "raw" => Some(LoadVarFormat::Raw),
+                _ => None,
+            },
+        );
+        let reveal = self.boolean(fields, "reveal");
+
+        let mut load_var = LoadVar::new(name?.as_str(), file?.as_str());
+        if let Some(format) = format {
+            load_var = load_var.with_format(format);
+        }
+        Some(load_var.with_reveal(reveal.unwrap_or(false)))
+    }
+
+    fn var_source(&mut self, path: &str, value: &Yaml) -> Option<VarSource> {
+        let mut fields = self.fields(path, value)?;
+        let name = self.required_string(&mut fields, "name");
+        let type_name = self.required_string(&mut fields, "type");
+        let config_path = fields.path("config");
+        let config = fields.take("config");
+        let config = self.required(&fields, "config", config);
+        self.done(fields);
+
+        let name = name?;
+        let mut fields = self.fields(&config_path, config?)?;
+        let var_source = match type_name?.as_str() {
+            "vault" => Some(VarSource::vault(
+                name.as_str(),
+                VaultConfig {
+                    url: self.required_string(&mut fields, "url").unwrap_or_default(),
+                    ca_cert: self.string(&mut fields, "ca_cert"),
+                    path_prefix: self.string(&mut fields, "path_prefix"),
+                    lookup_templates: self
+                        .strings(&mut fields, "lookup_templates")
+                        .unwrap_or_default(),
+                    shared_path: self.string(&mut fields, "shared_path"),
+                    namespace: self.string(&mut fields, "namespace"),
+                    client_cert: self.string(&mut fields, "client_cert"),
+                    client_key: self.string(&mut fields, "client_key"),
+                    client_token: self.string(&mut fields, "client_token"),
+                    auth_backend: self.string(&mut fields, "auth_backend"),
+                    auth_params: self
+                        .string_map(&mut fields, "auth_params")
+                        .unwrap_or_default(),
+                    auth_max_ttl: self.duration(&mut fields, "auth_max_ttl"),
+                    auth_retry_max: self.duration(&mut fields, "auth_retry_max"),
+                    auth_retry_initial: self.duration(&mut fields, "auth_retry_initial"),
+                    login_timeout: self.duration(&mut fields, "login_timeout"),
+                    query_timeout: self.duration(&mut fields, "query_timeout"),
+                },
+            )),
+            type_name @ ("ssm" | "secretsmanager") => {
+                let config = AwsConfig {
+                    region: self
+                        .required_string(&mut fields, "region")
+                        .unwrap_or_default(),
+                    access_key: self.string(&mut fields, "access_key"),
+                    secret_key: self.string(&mut fields, "secret_key"),
+                    session_token: self.string(&mut fields, "session_token"),
+                };
+                if type_name == "ssm" {
+                    Some(VarSource::ssm(name.as_str(), config))
+                } else {
+                    Some(VarSource::secrets_manager(name.as_str(), config))
+                }
+            }
+            "dummy" => {
+                let vars = self.string_map(&mut fields, "vars").unwrap_or_default();
+                Some(VarSource::Dummy {
+                    name,
+                    config: DummyConfig { vars },
+                })
+            }
+            type_name => {
+                self.problem(
+                    &join(path, "type"),
+                    &format!("Unsupported var source type '{}'", type_name),
+                );
+                return None;
+            }
+        };
+        self.done(fields);
+        var_source
+    }
+
+    fn group(&mut self, path: &str, value: &Yaml) -> Option<Group> {
+        let mut fields = self.fields(path, value)?;
+        let name = self.required_string(&mut fields, "name");
+        let jobs = self.strings(&mut fields, "jobs");
+        self.done(fields);
+
+        Some(Group {
+            name: name?,
+            jobs: jobs.unwrap_or_default(),
+        })
+    }
+
+    fn pipeline(&mut self, value: &Yaml) -> Option<Pipeline> {
+        let mut fields = self.fields("", value)?;
+        let background = fields.take("display").and_then(|display| {
+            let mut fields = self.fields("display", display)?;
+            let background = self.required_string(&mut fields, "background_image");
+            self.done(fields);
+            background
+        });
+
+        // Jobs refer to resources by name, which in turn refer to types.
+        let resource_types = self.named_list(&mut fields, "resource_types");
+        self.resource_types(&resource_types);
+        for (path, value) in self.named_list(&mut fields, "resources") {
+            if let Some(resource) = self.resource(&path, value) {
+                self.resources.insert(resource.name(), resource);
+            }
+        }
+        let var_sources = self
+            .named_list(&mut fields, "var_sources")
+            .into_iter()
+            .filter_map(|(path, value)| self.var_source(&path, value))
+            .collect();
+        let jobs = self
+            .named_list(&mut fields, "jobs")
+            .into_iter()
+            .filter_map(|(path, value)| self.job(&path, value))
+            .collect();
+        let groups = self
+            .named_list(&mut fields, "groups")
+            .into_iter()
+            .filter_map(|(path, value)| self.group(&path, value))
+            .collect();
+        self.done(fields);
+
+        let mut pipeline = Pipeline::new();
+        if let Some(background) = background {
+            pipeline = pipeline.with_background(background.as_str());
+        }
+        pipeline.jobs = jobs;
+        pipeline.resources = self.resources.values().cloned().collect();
+        pipeline.resource_types = self.resource_types.values().cloned().collect();
+        pipeline.var_sources = var_sources;
+        pipeline.groups = groups;
+        Some(pipeline)
+    }
+}
+
+fn parse<T, F: FnOnce(&mut Parser, &Yaml) -> Option<T>>(
+    yaml: &str,
+    mut parser: Parser,
+    parse_value: F,
+) -> Result<T, Errors> {
+    let value = serde_yaml::from_str::<Yaml>(yaml).map_err(Errors::SerdeError)?;
+    match parse_value(&mut parser, &value) {
+        Some(parsed) if parser.problems.is_empty() => Ok(parsed),
+        _ => Err(Errors::ParseErrors(parser.problems)),
+    }
+}
+
+fn with_resource_types(resource_types: &[ResourceTypes]) -> Parser {
+    Parser {
+        resource_types: resource_types
+            .iter()
+            .map(|resource_type| (resource_type.to_string(), resource_type.clone()))
+            .collect(),
+        ..Parser::default()
+    }
+}
+
+fn with_resources(resources: &[Resource]) -> Parser {
+    Parser {
+        resources: resources
+            .iter()
+            .map(|resource| (resource.name(), resource.clone()))
+            .collect(),
+        ..Parser::default()
+    }
+}
+
+// Parses hand-written pipelines, e.g., to migrate them to typed pipelines.
+// Resources are attached to the steps using them, as if the pipeline was
+// written with this crate, and are also kept as declared ones.
+pub fn parse_pipeline(yaml: &str) -> Result<Pipeline, Errors> {
+    parse(yaml, Parser::default(), |parser, value| {
+        parser.pipeline(value)
+    })
+}
+
+// Jobs and steps refer to resources by name, which must be in 'resources'.
+pub fn parse_job(yaml: &str, resources: &[Resource]) -> Result<Job, Errors> {
+    parse(yaml, with_resources(resources), |parser, value| {
+        parser.job("", value)
+    })
+}
+
+pub fn parse_step(yaml: &str, resources: &[Resource]) -> Result<Step, Errors> {
+    parse(yaml, with_resources(resources), |parser, value| {
+        parser.step("", value)
+    })
+}
+
+// Resources and task configs may use custom types from 'resource_types'.
+pub fn parse_resource(yaml: &str, resource_types: &[ResourceTypes]) -> Result<Resource, Errors> {
+    parse(
+        yaml,
+        with_resource_types(resource_types),
+        |parser, value| parser.resource("", value),
+    )
+}
+
+pub fn parse_resource_type(
+    yaml: &str,
+    resource_types: &[ResourceTypes],
+) -> Result<ResourceTypes, Errors> {
+    parse(
+        yaml,
+        with_resource_types(resource_types),
+        |parser, value| parser.resource_type("", value, &BTreeMap::new(), &mut vec![]),
+    )
+}
+
+pub fn parse_task_config(
+    yaml: &str,
+    resource_types: &[ResourceTypes],
+) -> Result<TaskConfig, Errors> {
+    parse(
+        yaml,
+        with_resource_types(resource_types),
+        |parser, value| parser.task_config("", value, false),
+    )
+}
@@ -1,6 +1,8 @@
 use crate::job::Job;
 use crate::resource::Resource;
 use crate::resource::ResourceTypes;
+use crate::schema::Identifier;
+use crate::var_source::VarSource;
 use serde::Serialize;
 
 #[derive(Debug, Clone, Serialize)]
@@ -8,6 +10,23 @@ pub(crate) struct DisplayConfig {
     pub(crate) background_image: String,
 }
 
+// https://concourse-ci.org/pipelines.html#schema.group_config
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Group {
+    pub(crate) name: Identifier,
+    pub(crate) jobs: Vec<Identifier>,
+}
+
+impl Group {
+    pub fn name(&self) -> Identifier {
+        self.name.clone()
+    }
+
+    pub fn jobs(&self) -> &[Identifier] {
+        &self.jobs
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct Pipeline {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -18,6 +37,16 @@ pub struct Pipeline {
     pub(crate) resources: Vec<Resource>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) resource_types: Vec<ResourceTypes>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) var_sources: Vec<VarSource>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) groups: Vec<Group>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Pipeline {
@@ -27,6 +56,8 @@ impl Pipeline {
             jobs: vec![],
             resources: vec![],
             resource_types: vec![],
+            var_sources: vec![],
+            groups: vec![],
         }
     }
 
@@ -52,7 +83,24 @@ impl Pipeline {
         self
     }
 
+    pub fn with_group(mut self, name: &str, jobs: &[&Job]) -> Self {
+        self.groups.push(Group {
+            name: name.to_string(),
+            jobs: jobs.iter().map(|job| job.name()).collect(),
+        });
+        self
+    }
+
+    pub fn with_var_source(mut self, var_source: &VarSource) -> Self {
+        self.var_sources.push(var_source.clone());
+        self
+    }
+
     pub fn jobs(&self) -> &[Job] {
         &self.jobs
     }
+
+    pub fn groups(&self) -> &[Group] {
+        &self.groups
+    }
 }
@@ -1,21 +1,54 @@
+use crate::get::GetParams;
 use crate::resource::Resource;
+use crate::resource::ResourceTypes;
+use crate::schema::to_config;
+use crate::schema::to_map;
+use crate::schema::Config;
 use crate::schema::Identifier;
+use crate::schema::Value;
 use crate::step::Step;
 use serde::ser::SerializeStruct;
 use serde::Serialize;
 use serde::Serializer;
 use std::collections::BTreeMap;
 
+// Params of a put step that only apply to a certain resource type, see
+// resource::community for examples.
+pub trait PutParams: Serialize {
+    fn resource_type(&self) -> ResourceTypes;
+}
+
+// https://concourse-ci.org/put-step.html#schema.put.inputs
+#[derive(Debug, Clone, PartialEq)]
+pub enum PutInputs {
+    All,
+    Detect,
+    List(Vec<Identifier>),
+}
+
+impl Serialize for PutInputs {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::All => serializer.serialize_str("all"),
+            Self::Detect => serializer.serialize_str("detect"),
+            Self::List(ref inputs) => inputs.serialize(serializer),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Put {
     pub(crate) put: Identifier,
     pub(crate) resource: Resource,
-    pub(crate) params: BTreeMap<String, String>,
+    pub(crate) params: Config,
+    pub(crate) inputs: Option<PutInputs>,
+    pub(crate) get_params: Config,
+    pub(crate) no_get: bool,
 }
 
 impl Serialize for Put {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let mut state = serializer.serialize_struct("PutStep", 5)?;
+        let mut state = serializer.serialize_struct("PutStep", 6)?;
         if !self.put.is_empty() {
             state.serialize_field("put", &self.put)?;
             state.serialize_field("resource", &self.resource.name())?;
@@ -27,6 +60,18 @@ impl Serialize for Put {
             state.serialize_field("params", &self.params)?;
         }
 
+        if let Some(ref inputs) = self.inputs {
+            state.serialize_field("inputs", inputs)?;
+        }
+
+        if !self.get_params.is_empty() {
+            state.serialize_field("get_params", &self.get_params)?;
+        }
+
+        if self.no_get {
+            state.serialize_field("no_get", &self.no_get)?;
+        }
+
         state.end()
     }
 }
@@ -37,14 +82,62 @@ impl Put {
             put: identifier.to_string(),
             resource: resource.clone(),
             params: BTreeMap::new(),
+            inputs: None,
+            get_params: BTreeMap::new(),
+            no_get: false,
+        }
+    }
+
+    pub fn with_params<V: Into<Value> + Clone>(mut self, params: &[(&str, V)]) -> Self {
+        self.params = to_map(params);
+        self
+    }
+
+    pub fn with_typed_params<P: PutParams>(mut self, params: &P) -> Self {
+        if !self.resource.type_.is_same_type(&params.resource_type()) {
+            panic!(
+                "The params of resource '{}' must be of type '{}', got '{}'",
+                self.resource.name(),
+                self.resource.type_,
+                params.resource_type()
+            );
+        }
+        self.params = to_config(params);
+        self
+    }
+
+    pub fn with_inputs(mut self, inputs: PutInputs) -> Self {
+        self.inputs = Some(inputs);
+        self
+    }
+
+    // Params of the implicit get that follows the put.
+    pub fn with_get_params<V: Into<Value> + Clone>(mut self, get_params: &[(&str, V)]) -> Self {
+        self.get_params = to_map(get_params);
+        self
+    }
+
+    pub fn with_typed_get_params<P: GetParams>(mut self, get_params: &P) -> Self {
+        if !self
+            .resource
+            .type_
+            .is_same_type(&get_params.resource_type())
+        {
+            panic!(
+                "The get params of resource '{}' must be of type '{}', got '{}'",
+                self.resource.name(),
+                self.resource.type_,
+                get_params.resource_type()
+            );
         }
+        self.get_params = to_config(get_params);
+        self
     }
 
-    pub fn with_params(mut self, params: &[(&str, &str)]) -> Self {
-        self.params = params
-            .iter()
-            .map(|(k, v)| (k.to_string(), v.to_string()))
-            .collect();
+    // Skips the implicit get, the resource then isn't available to the
+    // following steps.
+    pub fn with_no_get(mut self, no_get: bool) -> Self {
+        self.no_get = no_get;
         self
     }
 
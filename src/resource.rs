@@ -1,15 +1,27 @@
 use crate::get::Get;
 use crate::put::Put;
+use crate::schema::to_map;
 use crate::schema::Config;
+use crate::schema::Duration;
 use crate::schema::Identifier;
+use crate::schema::Value;
+use crate::schema::Var;
 use crate::schema::Version;
+use crate::source::DockerImageSource;
+use crate::source::GitSource;
+use crate::source::RegistryImageSource;
+use crate::source::Source;
+use crate::source::TypedSource;
 use crate::task::TaskResource;
-use git_url_parse::GitUrl;
 use serde::ser::SerializeStruct;
 use serde::Serialize;
 use serde::Serializer;
 use std::collections::BTreeMap;
+use std::fmt;
 
+pub mod community;
+
+// https://concourse-ci.org/resource-types.html
 #[derive(Debug, Clone, PartialEq)]
 pub enum ResourceTypes {
     DockerImage,
@@ -21,12 +33,17 @@ pub enum ResourceTypes {
         type_: Box<ResourceTypes>,
         source: Config,
         params: Config,
+        privileged: bool,
+        check_every: Option<CheckEvery>,
+        tags: Vec<String>,
+        // Source defaults of every resource of this type.
+        defaults: Config,
     },
 }
 
 impl Serialize for ResourceTypes {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let mut state = serializer.serialize_struct("Resource", 5)?;
+        let mut state = serializer.serialize_struct("Resource", 9)?;
         state.serialize_field("name", self.to_string().as_str())?;
 
         match &self {
@@ -34,6 +51,10 @@ impl Serialize for ResourceTypes {
                 ref type_,
                 ref source,
                 ref params,
+                ref privileged,
+                ref check_every,
+                ref tags,
+                ref defaults,
                 ..
             } => {
                 state.serialize_field("type", type_.to_string().as_str())?;
@@ -43,6 +64,18 @@ impl Serialize for ResourceTypes {
                 if !params.is_empty() {
                     state.serialize_field("params", params)?;
                 }
+                if *privileged {
+                    state.serialize_field("privileged", privileged)?;
+                }
+                if let Some(ref check_every) = check_every {
+                    state.serialize_field("check_every", check_every)?;
+                }
+                if !tags.is_empty() {
+                    state.serialize_field("tags", tags)?;
+                }
+                if !defaults.is_empty() {
+                    state.serialize_field("defaults", defaults)?;
+                }
             }
             _ => { /* Do nothing. */ }
         }
@@ -51,94 +84,159 @@ impl Serialize for ResourceTypes {
     }
 }
 
-impl ResourceTypes {
-    pub fn to_string(&self) -> String {
+impl fmt::Display for ResourceTypes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Git => String::from("git"),
-            Self::RegistryImage => String::from("registry-image"),
-            Self::DockerImage => String::from("docker-image"),
-            Self::Time => String::from("time"),
-            Self::Custom { ref name, .. } => name.clone(),
+            Self::Git => write!(f, "git"),
+            Self::RegistryImage => write!(f, "registry-image"),
+            Self::DockerImage => write!(f, "docker-image"),
+            Self::Time => write!(f, "time"),
+            Self::Custom { ref name, .. } => write!(f, "{}", name),
         }
     }
+}
 
+impl ResourceTypes {
     pub fn new(name: &str, type_: ResourceTypes) -> Self {
         Self::Custom {
             name: name.to_string(),
             type_: Box::new(type_),
             source: BTreeMap::new(),
             params: BTreeMap::new(),
+            privileged: false,
+            check_every: None,
+            tags: vec![],
+            defaults: BTreeMap::new(),
         }
     }
 
-    pub fn with_source(self, new_source: &[(&str, &str)]) -> Self {
+    pub fn with_source<V: Into<Value> + Clone>(mut self, new_source: &[(&str, V)]) -> Self {
         match self {
-            Self::Custom {
-                name,
-                type_,
-                mut source,
-                params,
-            } => {
-                new_source
-                    .iter()
-                    .map(|(k, v)| source.insert(k.to_string(), v.to_string()))
-                    .count();
-
-                Self::Custom {
-                    name,
-                    type_,
-                    source,
-                    params,
-                }
-            }
-            unsupported => panic!(
+            Self::Custom { ref mut source, .. } => source.extend(to_map(new_source)),
+            ref unsupported => panic!(
                 "Applying with_source() on resource type '{}' is not allowed",
-                unsupported.to_string()
+                unsupported
             ),
         }
+        self
     }
 
-    pub fn with_params(self, new_params: &[(&str, &str)]) -> Self {
+    pub fn with_params<V: Into<Value> + Clone>(mut self, new_params: &[(&str, V)]) -> Self {
+        match self {
+            Self::Custom { ref mut params, .. } => params.extend(to_map(new_params)),
+            ref unsupported => panic!(
+                "Applying with_params() on resource type '{}' is not allowed",
+                unsupported
+            ),
+        }
+        self
+    }
+
+    pub fn with_privileged(mut self, new_privileged: bool) -> Self {
         match self {
             Self::Custom {
-                name,
-                type_,
-                source,
-                mut params,
-            } => {
-                new_params
-                    .iter()
-                    .map(|(k, v)| params.insert(k.to_string(), v.to_string()))
-                    .count();
-
-                Self::Custom {
-                    name,
-                    type_,
-                    source,
-                    params,
-                }
+                ref mut privileged, ..
+            } => *privileged = new_privileged,
+            ref unsupported => panic!(
+                "Applying with_privileged() on resource type '{}' is not allowed",
+                unsupported
+            ),
+        }
+        self
+    }
+
+    pub fn with_check_every(mut self, new_check_every: CheckEvery) -> Self {
+        match self {
+            Self::Custom {
+                ref mut check_every,
+                ..
+            } => *check_every = Some(new_check_every),
+            ref unsupported => panic!(
+                "Applying with_check_every() on resource type '{}' is not allowed",
+                unsupported
+            ),
+        }
+        self
+    }
+
+    pub fn with_tags(mut self, new_tags: &[&str]) -> Self {
+        match self {
+            Self::Custom { ref mut tags, .. } => {
+                *tags = new_tags.iter().map(|t| t.to_string()).collect()
             }
-            unsupported => panic!(
-                "Applying with_params() on resource type '{}' is not allowed",
-                unsupported.to_string()
+            ref unsupported => panic!(
+                "Applying with_tags() on resource type '{}' is not allowed",
+                unsupported
             ),
         }
+        self
+    }
+
+    // Defaults are merged into the source of every resource of this type when
+    // the pipeline is cooked, see cook::ResourceTypeDefaults.
+    pub fn with_defaults<V: Into<Value> + Clone>(mut self, new_defaults: &[(&str, V)]) -> Self {
+        match self {
+            Self::Custom {
+                ref mut defaults, ..
+            } => defaults.extend(to_map(new_defaults)),
+            ref unsupported => panic!(
+                "Applying with_defaults() on resource type '{}' is not allowed",
+                unsupported
+            ),
+        }
+        self
+    }
+
+    // Custom types are the same type when they have the same name, however
+    // they are configured, e.g., with defaults or a pinned image.
+    pub(crate) fn is_same_type(&self, other: &ResourceTypes) -> bool {
+        self.to_string() == other.to_string()
+    }
+
+    pub fn defaults(&self) -> Option<&Config> {
+        match self {
+            Self::Custom { ref defaults, .. } => Some(defaults),
+            _ => None,
+        }
+    }
+}
+
+// https://concourse-ci.org/resources.html#schema.resource.check_every
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CheckEvery {
+    Never,
+    Interval(Duration),
+}
+
+impl Serialize for CheckEvery {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Never => serializer.serialize_str("never"),
+            Self::Interval(ref interval) => interval.serialize(serializer),
+        }
     }
 }
 
+// https://concourse-ci.org/resources.html
 #[derive(Debug, Clone)]
 pub struct Resource {
     pub(crate) name: Identifier,
     pub(crate) icon: Option<String>,
     pub(crate) type_: ResourceTypes,
-    pub(crate) source: Config,
+    pub(crate) source: Source,
     pub(crate) trigger: bool,
     pub(crate) version: Option<Version>,
+    pub(crate) check_every: Option<CheckEvery>,
+    pub(crate) check_timeout: Option<Duration>,
+    pub(crate) tags: Vec<String>,
+    pub(crate) public: bool,
+    pub(crate) webhook_token: Option<String>,
+    pub(crate) expose_build_created_by: bool,
 }
 
 impl Serialize for Resource {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let mut state = serializer.serialize_struct("Resource", 5)?;
+        let mut state = serializer.serialize_struct("Resource", 11)?;
         state.serialize_field("name", &self.name)?;
         state.serialize_field("type", self.resource_type().to_string().as_str())?;
         if self.icon.is_some() {
@@ -150,6 +248,24 @@ impl Serialize for Resource {
         if self.version.is_some() {
             state.serialize_field("version", &self.version)?;
         }
+        if let Some(ref check_every) = self.check_every {
+            state.serialize_field("check_every", check_every)?;
+        }
+        if let Some(ref check_timeout) = self.check_timeout {
+            state.serialize_field("check_timeout", check_timeout)?;
+        }
+        if !self.tags.is_empty() {
+            state.serialize_field("tags", &self.tags)?;
+        }
+        if self.public {
+            state.serialize_field("public", &self.public)?;
+        }
+        if let Some(ref webhook_token) = self.webhook_token {
+            state.serialize_field("webhook_token", webhook_token)?;
+        }
+        if self.expose_build_created_by {
+            state.serialize_field("expose_build_created_by", &self.expose_build_created_by)?;
+        }
         state.end()
     }
 }
@@ -160,9 +276,15 @@ impl Resource {
             name: name.to_string(),
             icon: None,
             type_: res_type.clone(),
-            source: BTreeMap::new(),
+            source: Source::default(),
             trigger: false,
             version: None,
+            check_every: None,
+            check_timeout: None,
+            tags: vec![],
+            public: false,
+            webhook_token: None,
+            expose_build_created_by: false,
         }
     }
 
@@ -170,68 +292,25 @@ impl Resource {
         self.trigger
     }
 
+    // Shorthands for common resources. Use the typed sources to set the other
+    // source fields, e.g., GitSource::to_resource(), or TimeSource for a typed
+    // interval.
     pub fn git(uri: &str, branch: &str) -> Self {
-        let git_url = GitUrl::parse(uri)
-            .expect(format!("The URI of given git resource '{}' is not valid", uri).as_str());
-
-        let mut source = BTreeMap::new();
-        source.insert(String::from("uri"), uri.to_string());
-        if branch != "" {
-            source.insert(String::from("branch"), branch.to_string());
-        }
-
-        let name = if branch == "" {
-            git_url.name
-        } else {
-            format!("{}.{}", git_url.name, branch)
-        };
-
-        Self {
-            name,
-            type_: ResourceTypes::Git,
-            icon: if uri.contains("github") {
-                Some(String::from("github"))
-            } else if uri.contains("gitlab") {
-                Some(String::from("gitlab"))
-            } else {
-                None
-            },
-            source,
-            trigger: false,
-            version: None,
-        }
+        GitSource::new(uri).with_branch(branch).to_resource()
     }
 
     pub fn time(interval: &str) -> Self {
-        Self {
-            name: format!("every-{}", interval),
-            type_: ResourceTypes::Time,
-            icon: Some(String::from("clock-outline")),
-            source: [("interval", interval)]
-                .iter()
-                .map(|(k, v)| (k.to_string(), v.to_string()))
-                .collect(),
-            trigger: false,
-            version: None,
-        }
+        Self::new(format!("every-{}", interval).as_str(), &ResourceTypes::Time)
+            .with_icon("clock-outline")
+            .with_source(&[("interval", interval)])
     }
 
     pub fn registry_image(repository: &str) -> Self {
-        Self {
-            name: repository.to_string(),
-            type_: ResourceTypes::RegistryImage,
-            icon: Some(String::from("docker")),
-            source: [("repository", repository)]
-                .iter()
-                .map(|(k, v)| (k.to_string(), v.to_string()))
-                .collect(),
-            trigger: false,
-            version: None,
-        }
+        RegistryImageSource::new(repository).to_resource()
     }
 
-    pub fn docker_image() -> Self {
-        todo!()
+    pub fn docker_image(repository: &str) -> Self {
+        DockerImageSource::new(repository).to_resource()
     }
 
     pub fn with_name(mut self, name: &str) -> Self {
@@ -240,7 +319,7 @@ impl Resource {
     }
 
     pub fn with_icon(mut self, icon: &str) -> Self {
-        self.icon = if icon == "" {
+        self.icon = if icon.is_empty() {
             None
         } else {
             Some(icon.to_string())
@@ -253,6 +332,36 @@ impl Resource {
         self
     }
 
+    pub fn with_check_every(mut self, check_every: CheckEvery) -> Self {
+        self.check_every = Some(check_every);
+        self
+    }
+
+    pub fn with_check_timeout(mut self, check_timeout: Duration) -> Self {
+        self.check_timeout = Some(check_timeout);
+        self
+    }
+
+    pub fn with_tags(mut self, tags: &[&str]) -> Self {
+        self.tags = tags.iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    pub fn with_public(mut self, public: bool) -> Self {
+        self.public = public;
+        self
+    }
+
+    pub fn with_webhook_token(mut self, webhook_token: &Var) -> Self {
+        self.webhook_token = Some(webhook_token.to_string());
+        self
+    }
+
+    pub fn with_expose_build_created_by(mut self, expose_build_created_by: bool) -> Self {
+        self.expose_build_created_by = expose_build_created_by;
+        self
+    }
+
     pub fn name(&self) -> Identifier {
         self.name.clone()
     }
@@ -261,15 +370,28 @@ impl Resource {
         &self.type_
     }
 
-    pub fn with_source(mut self, source: &[(&str, &str)]) -> Self {
-        source
-            .iter()
-            .map(|(k, v)| self.source.insert(k.to_string(), v.to_string()))
-            // Iterators are lazy, use .count() to evaluate it.
-            .count();
+    pub fn with_source<V: Into<Value> + Clone>(mut self, source: &[(&str, V)]) -> Self {
+        self.source.raw.extend(to_map(source));
         self
     }
 
+    pub fn with_typed_source(mut self, source: TypedSource) -> Self {
+        if !self.type_.is_same_type(&source.resource_type()) {
+            panic!(
+                "The source of resource '{}' must be of type '{}', got '{}'",
+                self.name,
+                self.type_,
+                source.resource_type()
+            );
+        }
+        self.source.typed = Some(source);
+        self
+    }
+
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
     pub fn as_get_resource(&self) -> Get {
         Get::from("", self, None)
     }
@@ -297,7 +419,7 @@ impl Resource {
 pub struct AnonymousResource {
     #[serde(rename(serialize = "type"))]
     type_: Identifier,
-    source: Config,
+    source: Source,
     #[serde(skip_serializing_if = "Option::is_none")]
     params: Option<Config>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -305,25 +427,17 @@ pub struct AnonymousResource {
 }
 
 impl AnonymousResource {
-    pub fn from(type_: &str, source: &[(&str, &str)]) -> Self {
+    pub fn from<V: Into<Value> + Clone>(type_: &str, source: &[(&str, V)]) -> Self {
         Self {
             type_: type_.to_string(),
-            source: source
-                .iter()
-                .map(|(k, v)| (k.to_string(), v.to_string()))
-                .collect(),
+            source: Source::raw(to_map(source)),
             params: None,
             version: None,
         }
     }
 
-    pub fn with_params(mut self, params: &[(&str, &str)]) -> Self {
-        self.params = Some(
-            params
-                .iter()
-                .map(|(k, v)| (k.to_string(), v.to_string()))
-                .collect(),
-        );
+    pub fn with_params<V: Into<Value> + Clone>(mut self, params: &[(&str, V)]) -> Self {
+        self.params = Some(to_map(params));
         self
     }
 
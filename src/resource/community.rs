@@ -0,0 +1,712 @@
+// Typed sources and put params of common community resource types. Each
+// source comes with its resource type, which cook declares in the pipeline.
+// Use Resource::with_name() to rename the resources from to_resource().
+use crate::put::PutParams;
+use crate::resource::Resource;
+use crate::resource::ResourceTypes;
+use crate::schema::boolean_is_false;
+use crate::schema::DirPath;
+use crate::schema::Duration;
+use crate::schema::FilePath;
+use crate::schema::Var;
+use crate::source::TypedSource;
+use serde::ser::SerializeMap;
+use serde::Serialize;
+use serde::Serializer;
+
+fn community_type(name: &str, repository: &str) -> ResourceTypes {
+    ResourceTypes::new(name, ResourceTypes::RegistryImage)
+        .with_source(&[("repository", repository)])
+}
+
+// https://github.com/cloudfoundry-community/slack-notification-resource
+pub fn slack_notification_type() -> ResourceTypes {
+    community_type(
+        "slack-notification",
+        "cfcommunity/slack-notification-resource",
+    )
+}
+
+// https://github.com/concourse/semver-resource
+pub fn semver_type() -> ResourceTypes {
+    community_type("semver", "concourse/semver-resource")
+}
+
+// https://github.com/concourse/s3-resource
+pub fn s3_type() -> ResourceTypes {
+    community_type("s3", "concourse/s3-resource")
+}
+
+// https://github.com/concourse/pool-resource
+pub fn pool_type() -> ResourceTypes {
+    community_type("pool", "concourse/pool-resource")
+}
+
+// https://github.com/concourse/github-release-resource
+pub fn github_release_type() -> ResourceTypes {
+    community_type("github-release", "concourse/github-release-resource")
+}
+
+// https://github.com/concourse/mock-resource
+pub fn mock_type() -> ResourceTypes {
+    community_type("mock", "concourse/mock-resource")
+}
+
+fn to_resource(name: &str, source: TypedSource, icon: &str) -> Resource {
+    Resource::new(name, &source.resource_type())
+        .with_icon(icon)
+        .with_typed_source(source)
+}
+
+// https://github.com/cloudfoundry-community/slack-notification-resource#source-configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SlackNotificationSource {
+    pub(crate) url: String,
+    #[serde(skip_serializing_if = "boolean_is_false")]
+    pub(crate) insecure: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) proxy: Option<String>,
+    #[serde(skip_serializing_if = "boolean_is_false")]
+    pub(crate) disable: bool,
+}
+
+impl SlackNotificationSource {
+    pub fn new(url: &Var) -> Self {
+        Self {
+            url: url.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_insecure(mut self, insecure: bool) -> Self {
+        self.insecure = insecure;
+        self
+    }
+
+    pub fn with_proxy(mut self, proxy: &str) -> Self {
+        self.proxy = Some(proxy.to_string());
+        self
+    }
+
+    pub fn with_disable(mut self, disable: bool) -> Self {
+        self.disable = disable;
+        self
+    }
+
+    // The resource is named 'slack'.
+    pub fn to_resource(self) -> Resource {
+        to_resource("slack", TypedSource::SlackNotification(self), "slack")
+    }
+}
+
+// https://github.com/cloudfoundry-community/slack-notification-resource#out-sends-message-to-slack
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SlackNotificationParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) text_file: Option<FilePath>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) channel: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) icon_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) icon_emoji: Option<String>,
+    #[serde(skip_serializing_if = "boolean_is_false")]
+    pub(crate) silent: bool,
+    #[serde(skip_serializing_if = "boolean_is_false")]
+    pub(crate) always_notify: bool,
+}
+
+impl SlackNotificationParams {
+    pub fn text(text: &str) -> Self {
+        Self {
+            text: Some(text.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn text_file(text_file: &str) -> Self {
+        Self {
+            text_file: Some(text_file.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_channel(mut self, channel: &str) -> Self {
+        self.channel = Some(channel.to_string());
+        self
+    }
+
+    pub fn with_username(mut self, username: &str) -> Self {
+        self.username = Some(username.to_string());
+        self
+    }
+
+    pub fn with_icon_url(mut self, icon_url: &str) -> Self {
+        self.icon_url = Some(icon_url.to_string());
+        self
+    }
+
+    pub fn with_icon_emoji(mut self, icon_emoji: &str) -> Self {
+        self.icon_emoji = Some(icon_emoji.to_string());
+        self
+    }
+
+    pub fn with_silent(mut self, silent: bool) -> Self {
+        self.silent = silent;
+        self
+    }
+
+    pub fn with_always_notify(mut self, always_notify: bool) -> Self {
+        self.always_notify = always_notify;
+        self
+    }
+}
+
+impl PutParams for SlackNotificationParams {
+    fn resource_type(&self) -> ResourceTypes {
+        slack_notification_type()
+    }
+}
+
+// https://github.com/concourse/semver-resource#source-configuration
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "driver", rename_all = "lowercase")]
+pub enum SemverDriver {
+    Git {
+        uri: String,
+        branch: String,
+        file: FilePath,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        private_key: Option<String>,
+    },
+    S3 {
+        bucket: String,
+        key: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        access_key_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        secret_access_key: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        region_name: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SemverSource {
+    #[serde(flatten)]
+    pub(crate) driver: SemverDriver,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) initial_version: Option<String>,
+}
+
+impl SemverSource {
+    // Stores the version in 'file' of the given branch of a git repository.
+    pub fn git(uri: &str, branch: &str, file: &str) -> Self {
+        Self {
+            driver: SemverDriver::Git {
+                uri: uri.to_string(),
+                branch: branch.to_string(),
+                file: file.to_string(),
+                private_key: None,
+            },
+            initial_version: None,
+        }
+    }
+
+    // Stores the version in the object 'key' of an S3 bucket.
+    pub fn s3(bucket: &str, key: &str) -> Self {
+        Self {
+            driver: SemverDriver::S3 {
+                bucket: bucket.to_string(),
+                key: key.to_string(),
+                access_key_id: None,
+                secret_access_key: None,
+                region_name: None,
+            },
+            initial_version: None,
+        }
+    }
+
+    pub fn with_initial_version(mut self, initial_version: &str) -> Self {
+        self.initial_version = Some(initial_version.to_string());
+        self
+    }
+
+    pub fn with_private_key(mut self, key: &Var) -> Self {
+        match self.driver {
+            SemverDriver::Git {
+                ref mut private_key,
+                ..
+            } => *private_key = Some(key.to_string()),
+            _ => panic!("with_private_key() can only be used with the git driver of semver"),
+        }
+        self
+    }
+
+    pub fn with_credentials(mut self, access_key: &Var, secret_key: &Var) -> Self {
+        match self.driver {
+            SemverDriver::S3 {
+                ref mut access_key_id,
+                ref mut secret_access_key,
+                ..
+            } => {
+                *access_key_id = Some(access_key.to_string());
+                *secret_access_key = Some(secret_key.to_string());
+            }
+            _ => panic!("with_credentials() can only be used with the s3 driver of semver"),
+        }
+        self
+    }
+
+    pub fn with_region_name(mut self, region: &str) -> Self {
+        match self.driver {
+            SemverDriver::S3 {
+                ref mut region_name,
+                ..
+            } => *region_name = Some(region.to_string()),
+            _ => panic!("with_region_name() can only be used with the s3 driver of semver"),
+        }
+        self
+    }
+
+    // The resource is named 'version'.
+    pub fn to_resource(self) -> Resource {
+        to_resource("version", TypedSource::Semver(self), "tag")
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SemverBump {
+    Major,
+    Minor,
+    Patch,
+    Final,
+}
+
+// https://github.com/concourse/semver-resource#out-set-the-version-or-bump-the-current-one
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SemverParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) file: Option<FilePath>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) bump: Option<SemverBump>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) pre: Option<String>,
+}
+
+impl SemverParams {
+    // Sets the version to the content of 'file'.
+    pub fn file(file: &str) -> Self {
+        Self {
+            file: Some(file.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn bump(bump: SemverBump) -> Self {
+        Self {
+            bump: Some(bump),
+            ..Self::default()
+        }
+    }
+
+    // Bumps to a pre-release, e.g., 'rc' turns '1.2.3' into '1.2.3-rc.1'.
+    pub fn pre(pre: &str) -> Self {
+        Self::default().with_pre(pre)
+    }
+
+    pub fn with_pre(mut self, pre: &str) -> Self {
+        self.pre = Some(pre.to_string());
+        self
+    }
+}
+
+impl PutParams for SemverParams {
+    fn resource_type(&self) -> ResourceTypes {
+        semver_type()
+    }
+}
+
+// https://github.com/concourse/s3-resource#source-configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct S3Source {
+    pub(crate) bucket: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) regexp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) versioned_file: Option<FilePath>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) access_key_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) secret_access_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) region_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) endpoint: Option<String>,
+}
+
+impl S3Source {
+    // Versions objects by the version captured by the regexp, e.g.,
+    // 'releases/app-(.*).tgz'.
+    pub fn regexp(bucket: &str, regexp: &str) -> Self {
+        Self {
+            bucket: bucket.to_string(),
+            regexp: Some(regexp.to_string()),
+            ..Self::default()
+        }
+    }
+
+    // Versions an object by the versioning of the bucket.
+    pub fn versioned_file(bucket: &str, versioned_file: &str) -> Self {
+        Self {
+            bucket: bucket.to_string(),
+            versioned_file: Some(versioned_file.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_credentials(mut self, access_key_id: &Var, secret_access_key: &Var) -> Self {
+        self.access_key_id = Some(access_key_id.to_string());
+        self.secret_access_key = Some(secret_access_key.to_string());
+        self
+    }
+
+    pub fn with_region_name(mut self, region_name: &str) -> Self {
+        self.region_name = Some(region_name.to_string());
+        self
+    }
+
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = Some(endpoint.to_string());
+        self
+    }
+
+    // The resource is named after the bucket.
+    pub fn to_resource(self) -> Resource {
+        let name = self.bucket.clone();
+        to_resource(name.as_str(), TypedSource::S3(self), "bucket")
+    }
+}
+
+// https://github.com/concourse/s3-resource#out-upload-an-object
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct S3Params {
+    pub(crate) file: FilePath,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) acl: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) content_type: Option<String>,
+}
+
+impl S3Params {
+    // Uploads the file matching the glob 'file'.
+    pub fn new(file: &str) -> Self {
+        Self {
+            file: file.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_acl(mut self, acl: &str) -> Self {
+        self.acl = Some(acl.to_string());
+        self
+    }
+
+    pub fn with_content_type(mut self, content_type: &str) -> Self {
+        self.content_type = Some(content_type.to_string());
+        self
+    }
+}
+
+impl PutParams for S3Params {
+    fn resource_type(&self) -> ResourceTypes {
+        s3_type()
+    }
+}
+
+// https://github.com/concourse/pool-resource#source-configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PoolSource {
+    pub(crate) uri: String,
+    pub(crate) branch: String,
+    pub(crate) pool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) private_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) retry_delay: Option<Duration>,
+}
+
+impl PoolSource {
+    pub fn new(uri: &str, branch: &str, pool: &str) -> Self {
+        Self {
+            uri: uri.to_string(),
+            branch: branch.to_string(),
+            pool: pool.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_private_key(mut self, private_key: &Var) -> Self {
+        self.private_key = Some(private_key.to_string());
+        self
+    }
+
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = Some(retry_delay);
+        self
+    }
+
+    // The resource is named after the pool.
+    pub fn to_resource(self) -> Resource {
+        let name = self.pool.clone();
+        to_resource(name.as_str(), TypedSource::Pool(self), "lock")
+    }
+}
+
+// https://github.com/concourse/pool-resource#out-modify-a-pool-of-locks
+#[derive(Debug, Clone, PartialEq)]
+pub enum PoolAction {
+    // Acquires any unclaimed lock of the pool.
+    Acquire,
+    // Claims the lock with the given name.
+    Claim(String),
+    // Releases the lock fetched into the given directory.
+    Release(DirPath),
+    Add(DirPath),
+    AddClaimed(DirPath),
+    Remove(DirPath),
+    Update(DirPath),
+}
+
+impl Serialize for PoolAction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_map(Some(1))?;
+        match self {
+            Self::Acquire => state.serialize_entry("acquire", &true)?,
+            Self::Claim(ref name) => state.serialize_entry("claim", name)?,
+            Self::Release(ref dir) => state.serialize_entry("release", dir)?,
+            Self::Add(ref dir) => state.serialize_entry("add", dir)?,
+            Self::AddClaimed(ref dir) => state.serialize_entry("add_claimed", dir)?,
+            Self::Remove(ref dir) => state.serialize_entry("remove", dir)?,
+            Self::Update(ref dir) => state.serialize_entry("update", dir)?,
+        }
+        state.end()
+    }
+}
+
+impl PutParams for PoolAction {
+    fn resource_type(&self) -> ResourceTypes {
+        pool_type()
+    }
+}
+
+// https://github.com/concourse/github-release-resource#source-configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GithubReleaseSource {
+    pub(crate) owner: String,
+    pub(crate) repository: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) access_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) github_api_url: Option<String>,
+    #[serde(skip_serializing_if = "boolean_is_false")]
+    pub(crate) pre_release: bool,
+    #[serde(skip_serializing_if = "boolean_is_false")]
+    pub(crate) drafts: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) tag_filter: Option<String>,
+}
+
+impl GithubReleaseSource {
+    pub fn new(owner: &str, repository: &str) -> Self {
+        Self {
+            owner: owner.to_string(),
+            repository: repository.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_access_token(mut self, access_token: &Var) -> Self {
+        self.access_token = Some(access_token.to_string());
+        self
+    }
+
+    pub fn with_github_api_url(mut self, github_api_url: &str) -> Self {
+        self.github_api_url = Some(github_api_url.to_string());
+        self
+    }
+
+    pub fn with_pre_release(mut self, pre_release: bool) -> Self {
+        self.pre_release = pre_release;
+        self
+    }
+
+    pub fn with_drafts(mut self, drafts: bool) -> Self {
+        self.drafts = drafts;
+        self
+    }
+
+    pub fn with_tag_filter(mut self, tag_filter: &str) -> Self {
+        self.tag_filter = Some(tag_filter.to_string());
+        self
+    }
+
+    // The resource is named after the repository, e.g., 'concourse-release'.
+    pub fn to_resource(self) -> Resource {
+        let name = format!("{}-release", self.repository);
+        to_resource(name.as_str(), TypedSource::GithubRelease(self), "github")
+    }
+}
+
+// https://github.com/concourse/github-release-resource#out-create-a-release
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GithubReleaseParams {
+    pub(crate) name: FilePath,
+    pub(crate) tag: FilePath,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) tag_prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) commitish: Option<FilePath>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) body: Option<FilePath>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) globs: Vec<String>,
+}
+
+impl GithubReleaseParams {
+    // The name and the tag of the release are read from the given files.
+    pub fn new(name: &str, tag: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            tag: tag.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_tag_prefix(mut self, tag_prefix: &str) -> Self {
+        self.tag_prefix = Some(tag_prefix.to_string());
+        self
+    }
+
+    pub fn with_commitish(mut self, commitish: &str) -> Self {
+        self.commitish = Some(commitish.to_string());
+        self
+    }
+
+    pub fn with_body(mut self, body: &str) -> Self {
+        self.body = Some(body.to_string());
+        self
+    }
+
+    pub fn with_globs(mut self, globs: &[&str]) -> Self {
+        self.globs = globs.iter().map(|g| g.to_string()).collect();
+        self
+    }
+}
+
+impl PutParams for GithubReleaseParams {
+    fn resource_type(&self) -> ResourceTypes {
+        github_release_type()
+    }
+}
+
+// https://github.com/concourse/mock-resource#source-configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MockSource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) initial_version: Option<String>,
+    #[serde(skip_serializing_if = "boolean_is_false")]
+    pub(crate) no_initial_version: bool,
+    #[serde(skip_serializing_if = "boolean_is_false")]
+    pub(crate) mirror_self: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) force_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) check_delay: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) log: Option<String>,
+}
+
+impl MockSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_initial_version(mut self, initial_version: &str) -> Self {
+        self.initial_version = Some(initial_version.to_string());
+        self
+    }
+
+    pub fn with_no_initial_version(mut self, no_initial_version: bool) -> Self {
+        self.no_initial_version = no_initial_version;
+        self
+    }
+
+    pub fn with_mirror_self(mut self, mirror_self: bool) -> Self {
+        self.mirror_self = mirror_self;
+        self
+    }
+
+    pub fn with_force_version(mut self, force_version: &str) -> Self {
+        self.force_version = Some(force_version.to_string());
+        self
+    }
+
+    pub fn with_check_delay(mut self, check_delay: Duration) -> Self {
+        self.check_delay = Some(check_delay);
+        self
+    }
+
+    pub fn with_log(mut self, log: &str) -> Self {
+        self.log = Some(log.to_string());
+        self
+    }
+
+    // The resource is named 'mock'.
+    pub fn to_resource(self) -> Resource {
+        to_resource("mock", TypedSource::Mock(self), "")
+    }
+}
+
+// https://github.com/concourse/mock-resource#out-update-the-version
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MockParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) file: Option<FilePath>,
+    #[serde(skip_serializing_if = "boolean_is_false")]
+    pub(crate) print_env: bool,
+}
+
+impl MockParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_string());
+        self
+    }
+
+    pub fn with_file(mut self, file: &str) -> Self {
+        self.file = Some(file.to_string());
+        self
+    }
+
+    pub fn with_print_env(mut self, print_env: bool) -> Self {
+        self.print_env = print_env;
+        self
+    }
+}
+
+impl PutParams for MockParams {
+    fn resource_type(&self) -> ResourceTypes {
+        mock_type()
+    }
+}
@@ -1,6 +1,8 @@
 use std::collections::BTreeMap;
 
 use serde::Serialize;
+use serde::Serializer;
+use std::fmt;
 
 // https://concourse-ci.org/config-basics.html#schema.identifier
 pub type Identifier = String;
@@ -8,19 +10,524 @@ pub type Identifier = String;
 pub type FilePath = String;
 // https://concourse-ci.org/config-basics.html#schema.dir-path
 pub type DirPath = String;
+// https://concourse-ci.org/config-basics.html#schema.value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Value {
+    Bool(bool),
+    Number(Number),
+    Float(f64),
+    String(String),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+}
+
+impl Value {
+    pub fn list<V: Into<Value> + Clone>(values: &[V]) -> Self {
+        Self::List(values.iter().map(|v| v.clone().into()).collect())
+    }
+
+    pub fn map<V: Into<Value> + Clone>(entries: &[(&str, V)]) -> Self {
+        Self::Map(to_map(entries))
+    }
+
+    // Nulls have no counterpart and are dropped, as are map entries whose key
+    // isn't a string.
+    pub(crate) fn from_yaml(value: serde_yaml::Value) -> Option<Self> {
+        match value {
+            serde_yaml::Value::Null => None,
+            serde_yaml::Value::Bool(b) => Some(Self::Bool(b)),
+            serde_yaml::Value::Number(n) => match n.as_i64() {
+                Some(n) => Some(Self::Number(n)),
+                None => n.as_f64().map(Self::Float),
+            },
+            serde_yaml::Value::String(s) => Some(Self::String(s)),
+            serde_yaml::Value::Sequence(seq) => Some(Self::List(
+                seq.into_iter().filter_map(Self::from_yaml).collect(),
+            )),
+            serde_yaml::Value::Mapping(map) => Some(Self::Map(
+                map.into_iter()
+                    .filter_map(|(k, v)| match k {
+                        serde_yaml::Value::String(k) => Self::from_yaml(v).map(|v| (k, v)),
+                        _ => None,
+                    })
+                    .collect(),
+            )),
+            serde_yaml::Value::Tagged(tagged) => Self::from_yaml(tagged.value),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(n: i32) -> Self {
+        Self::Number(n.into())
+    }
+}
+
+impl From<Number> for Value {
+    fn from(n: Number) -> Self {
+        Self::Number(n)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(f: f64) -> Self {
+        Self::Float(f)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Self::String(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+impl From<&String> for Value {
+    fn from(s: &String) -> Self {
+        Self::String(s.clone())
+    }
+}
+
+impl From<&Var> for Value {
+    fn from(var: &Var) -> Self {
+        Self::String(var.to_string())
+    }
+}
+
+impl From<Var> for Value {
+    fn from(var: Var) -> Self {
+        Self::String(var.to_string())
+    }
+}
+
+impl<V: Into<Value>> From<Vec<V>> for Value {
+    fn from(values: Vec<V>) -> Self {
+        Self::List(values.into_iter().map(|v| v.into()).collect())
+    }
+}
+
+impl From<BTreeMap<String, Value>> for Value {
+    fn from(map: BTreeMap<String, Value>) -> Self {
+        Self::Map(map)
+    }
+}
+
+pub(crate) fn to_map<V: Into<Value> + Clone>(entries: &[(&str, V)]) -> BTreeMap<String, Value> {
+    entries
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.clone().into()))
+        .collect()
+}
+
+// Converts a typed struct, e.g., the params of a resource, into a config.
+pub(crate) fn to_config<T: Serialize>(typed: &T) -> Config {
+    match serde_yaml::to_value(typed).ok().and_then(Value::from_yaml) {
+        Some(Value::Map(config)) => config,
+        _ => panic!("Only structs and maps can be converted into a config"),
+    }
+}
+
 // https://concourse-ci.org/config-basics.html#schema.config
-pub type Config = BTreeMap<String, String>;
+pub type Config = BTreeMap<String, Value>;
 // https://concourse-ci.org/config-basics.html#schema.vars
-pub type Vars = BTreeMap<String, String>;
+pub type Vars = BTreeMap<String, Value>;
 // https://concourse-ci.org/config-basics.html#schema.env-vars
-pub type EnvVars = BTreeMap<String, String>;
+pub type EnvVars = BTreeMap<String, Value>;
 // https://concourse-ci.org/config-basics.html#schema.version
-#[derive(Debug, Clone, Serialize)]
-#[serde(rename_all = "lowercase")]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Version {
     Latest,
     Every,
     Custom(BTreeMap<String, String>),
 }
+
+impl Serialize for Version {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Latest => serializer.serialize_str("latest"),
+            Self::Every => serializer.serialize_str("every"),
+            Self::Custom(ref version) => version.serialize(serializer),
+        }
+    }
+}
+
+impl Version {
+    pub fn custom(version: &[(&str, &str)]) -> Self {
+        Self::Custom(
+            version
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+}
 // https://concourse-ci.org/config-basics.html#schema.number
 pub type Number = i64;
+// https://concourse-ci.org/config-basics.html#schema.duration
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Duration(std::time::Duration);
+
+// The constructors panic if the duration doesn't fit in a u64 of seconds.
+impl Duration {
+    pub fn hours(hours: u64) -> Self {
+        Self::seconds(
+            hours
+                .checked_mul(60 * 60)
+                .unwrap_or_else(|| panic!("Duration of {} hours is too long", hours)),
+        )
+    }
+
+    pub fn minutes(minutes: u64) -> Self {
+        Self::seconds(
+            minutes
+                .checked_mul(60)
+                .unwrap_or_else(|| panic!("Duration of {} minutes is too long", minutes)),
+        )
+    }
+
+    pub fn seconds(seconds: u64) -> Self {
+        Self(std::time::Duration::from_secs(seconds))
+    }
+
+    pub fn milliseconds(milliseconds: u64) -> Self {
+        Self(std::time::Duration::from_millis(milliseconds))
+    }
+
+    // Parses Go durations, e.g., '1h30m', '1.5h', '500us' or '0', including
+    // the format written by Display. Durations that don't fit are rejected.
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        match s {
+            "" => return None,
+            "0" => return Some(Self(std::time::Duration::ZERO)),
+            _ => { /* Do nothing. */ }
+        }
+        let is_number = |c: char| c.is_ascii_digit() || c == '.';
+        let mut nanos: u128 = 0;
+        let mut rest = s;
+        while !rest.is_empty() {
+            let number = rest.find(|c| !is_number(c)).unwrap_or(rest.len());
+            let (integer, fraction) = rest[..number]
+                .split_once('.')
+                .unwrap_or((&rest[..number], ""));
+            if integer.is_empty() && fraction.is_empty() {
+                return None;
+            }
+            rest = &rest[number..];
+            let unit = rest.find(is_number).unwrap_or(rest.len());
+            let scale: u128 = match &rest[..unit] {
+                "h" => 60 * 60 * 1_000_000_000,
+                "m" => 60 * 1_000_000_000,
+                "s" => 1_000_000_000,
+                "ms" => 1_000_000,
+                "us" | "µs" | "μs" => 1_000,
+                "ns" => 1,
+                _ => return None,
+            };
+            rest = &rest[unit..];
+
+            let mut value = match integer {
+                "" => 0,
+                _ => integer.parse::<u128>().ok()?.checked_mul(scale)?,
+            };
+            // Digits below a nanosecond are dropped, as Go does.
+            let mut place = scale;
+            for digit in fraction.chars() {
+                place /= 10;
+                value += digit.to_digit(10)? as u128 * place;
+            }
+            nanos = nanos.checked_add(value)?;
+        }
+        let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+        Some(Self(std::time::Duration::new(
+            secs,
+            (nanos % 1_000_000_000) as u32,
+        )))
+    }
+}
+
+impl From<std::time::Duration> for Duration {
+    fn from(duration: std::time::Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl std::ops::Add for Duration {
+    type Output = Duration;
+
+    fn add(self, other: Duration) -> Duration {
+        Self(self.0 + other.0)
+    }
+}
+
+// Formats the duration in the Go's time.ParseDuration format, e.g., '1h30m'.
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_zero() {
+            return write!(f, "0s");
+        }
+        let secs = self.0.as_secs();
+        let nanos = self.0.subsec_nanos() as u64;
+        for (value, unit) in [
+            (secs / 3600, "h"),
+            (secs % 3600 / 60, "m"),
+            (secs % 60, "s"),
+            (nanos / 1_000_000, "ms"),
+            (nanos / 1_000 % 1_000, "us"),
+            (nanos % 1_000, "ns"),
+        ] {
+            if value != 0 {
+                write!(f, "{}{}", value, unit)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Duration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+// A size in bytes, e.g., the memory limit of a container. Units are powers of
+// 1024, which is how Concourse parses them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ByteSize(u64);
+
+// The constructors panic if the size doesn't fit in a u64 of bytes.
+impl ByteSize {
+    pub fn bytes(bytes: u64) -> Self {
+        Self(bytes)
+    }
+
+    pub fn kilobytes(kilobytes: u64) -> Self {
+        Self::with_unit(kilobytes, 1 << 10, "KB")
+    }
+
+    pub fn megabytes(megabytes: u64) -> Self {
+        Self::with_unit(megabytes, 1 << 20, "MB")
+    }
+
+    pub fn gigabytes(gigabytes: u64) -> Self {
+        Self::with_unit(gigabytes, 1 << 30, "GB")
+    }
+
+    fn with_unit(value: u64, unit: u64, name: &str) -> Self {
+        Self(
+            value
+                .checked_mul(unit)
+                .unwrap_or_else(|| panic!("Size of {}{} is too large", value, name)),
+        )
+    }
+
+    // Parses the format written by Display, e.g., '512MB' or '1000'. Sizes
+    // that don't fit are rejected.
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let value = s[..digits].parse::<u64>().ok()?;
+        let unit: u64 = match s[digits..].to_ascii_uppercase().as_str() {
+            "" => 1,
+            "KB" => 1 << 10,
+            "MB" => 1 << 20,
+            "GB" => 1 << 30,
+            _ => return None,
+        };
+        value.checked_mul(unit).map(Self)
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Use the largest unit that represents the size exactly.
+        for (unit, size) in [("GB", 1 << 30), ("MB", 1 << 20), ("KB", 1 << 10)] {
+            if self.0 != 0 && self.0.is_multiple_of(size) {
+                return write!(f, "{}{}", self.0 / size, unit);
+            }
+        }
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Serialize for ByteSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+pub(crate) fn boolean_is_false(b: &bool) -> bool {
+    !*b
+}
+
+// https://concourse-ci.org/vars.html
+// Var dereferences to its '((...))' form, so it can be used anywhere a string
+// is accepted, e.g., `.with_source(&[("password", &password)])`. Typed sources
+// take credentials as vars, so they never end up in the pipeline in plain text.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Var {
+    pub(crate) source: Option<Identifier>,
+    pub(crate) path: String,
+    pub(crate) fields: Vec<String>,
+    rendered: String,
+}
+
+// Local vars are set by the load_var step and are referenced as '((.:name))'.
+pub(crate) const LOCAL_VAR_SOURCE: &str = ".";
+
+impl Var {
+    // Creates a var that is resolved by the credential manager or the
+    // 'fly set-pipeline --var' flags, i.e., '((name))'.
+    pub fn new(name: &str) -> Self {
+        Self::from_parts(None, name, vec![])
+    }
+
+    pub fn local(name: &str) -> Self {
+        Self::from_parts(Some(LOCAL_VAR_SOURCE), name, vec![])
+    }
+
+    pub(crate) fn from_parts(source: Option<&str>, path: &str, fields: Vec<String>) -> Self {
+        let mut this = Self {
+            source: source.map(|s| s.to_string()),
+            path: path.to_string(),
+            fields,
+            rendered: String::new(),
+        };
+        this.rendered = this.render();
+        this
+    }
+
+    pub fn field(mut self, field: &str) -> Self {
+        self.fields.push(field.to_string());
+        self.rendered = self.render();
+        self
+    }
+
+    pub fn name(&self) -> String {
+        self.path.clone()
+    }
+
+    fn render(&self) -> String {
+        let segments = std::iter::once(&self.path)
+            .chain(self.fields.iter())
+            .map(|segment| {
+                if segment.contains('.') {
+                    format!("\"{}\"", segment)
+                } else {
+                    segment.clone()
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(".");
+        match self.source {
+            Some(ref source) => format!("(({}:{}))", source, segments),
+            None => format!("(({}))", segments),
+        }
+    }
+
+    pub(crate) fn is_local(&self) -> bool {
+        self.source.as_deref() == Some(LOCAL_VAR_SOURCE)
+    }
+
+    // Parses a var reference without the surrounding parentheses, e.g.,
+    // 'source:path.field' or 'path."dotted.field"'.
+    fn parse(reference: &str) -> Option<Self> {
+        let reference = reference.trim();
+        let (source, rest) = match reference.split_once(':') {
+            Some((source, rest)) if !source.contains('"') => {
+                (Some(source.trim().to_string()), rest.trim())
+            }
+            _ => (None, reference),
+        };
+
+        let mut segments = vec![];
+        let mut segment = String::new();
+        let mut quoted = false;
+        for c in rest.chars() {
+            match c {
+                '"' => quoted = !quoted,
+                '.' if !quoted => segments.push(std::mem::take(&mut segment)),
+                _ => segment.push(c),
+            }
+        }
+        segments.push(segment);
+
+        let mut segments = segments.into_iter();
+        match segments.next() {
+            Some(path) if !path.is_empty() => Some(Self::from_parts(
+                source.as_deref(),
+                path.as_str(),
+                segments.collect(),
+            )),
+            _ => None,
+        }
+    }
+
+    // Returns all var references '((...))' in the given string.
+    pub(crate) fn find_all(s: &str) -> Vec<Self> {
+        let mut vars = vec![];
+        let mut rest = s;
+        while let Some(begin) = rest.find("((") {
+            rest = &rest[begin + 2..];
+            match rest.find("))") {
+                Some(end) => {
+                    if let Some(var) = Self::parse(&rest[..end]) {
+                        vars.push(var);
+                    }
+                    rest = &rest[end + 2..];
+                }
+                None => break,
+            }
+        }
+        vars
+    }
+
+    // Returns all var references in the serialized form of the given value.
+    pub(crate) fn find_all_in<T: Serialize>(value: &T) -> Result<Vec<Self>, serde_yaml::Error> {
+        fn walk(value: &serde_yaml::Value, vars: &mut Vec<Var>) {
+            match value {
+                serde_yaml::Value::String(s) => vars.append(&mut Var::find_all(s)),
+                serde_yaml::Value::Sequence(seq) => seq.iter().for_each(|v| walk(v, vars)),
+                serde_yaml::Value::Mapping(map) => map.iter().for_each(|(k, v)| {
+                    walk(k, vars);
+                    walk(v, vars);
+                }),
+                serde_yaml::Value::Tagged(tagged) => walk(&tagged.value, vars),
+                _ => { /* Do nothing. */ }
+            }
+        }
+
+        let mut vars = vec![];
+        walk(&serde_yaml::to_value(value)?, &mut vars);
+        Ok(vars)
+    }
+}
+
+impl fmt::Display for Var {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.rendered)
+    }
+}
+
+impl std::ops::Deref for Var {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.rendered.as_str()
+    }
+}
+
+impl Serialize for Var {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.rendered.as_str())
+    }
+}
@@ -0,0 +1,110 @@
+use crate::pipeline::Pipeline;
+use crate::schema::to_map;
+use crate::schema::FilePath;
+use crate::schema::Identifier;
+use crate::schema::Value;
+use crate::schema::Vars;
+use crate::step::Step;
+use serde::ser::SerializeStruct;
+use serde::Serialize;
+use serde::Serializer;
+
+// https://concourse-ci.org/set-pipeline-step.html
+#[derive(Debug, Clone)]
+pub struct SetPipeline {
+    pub(crate) set_pipeline: Identifier,
+    pub(crate) file: FilePath,
+    pub(crate) vars: Option<Vars>,
+    pub(crate) var_files: Option<Vec<FilePath>>,
+    pub(crate) instance_vars: Option<Vars>,
+    pub(crate) team: Option<Identifier>,
+    // The nested pipeline is cooked into 'file', it shouldn't be serialized!!
+    pub(crate) pipeline: Option<Box<Pipeline>>,
+}
+
+impl Serialize for SetPipeline {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("SetPipelineStep", 6)?;
+        state.serialize_field("set_pipeline", &self.set_pipeline)?;
+        state.serialize_field("file", &self.file)?;
+
+        if let Some(ref instance_vars) = self.instance_vars {
+            state.serialize_field("instance_vars", instance_vars)?;
+        }
+
+        if let Some(ref vars) = self.vars {
+            state.serialize_field("vars", vars)?;
+        }
+
+        if let Some(ref var_files) = self.var_files {
+            state.serialize_field("var_files", var_files)?;
+        }
+
+        if let Some(ref team) = self.team {
+            state.serialize_field("team", team)?;
+        }
+
+        state.end()
+    }
+}
+
+impl SetPipeline {
+    pub fn new(name: &str, file: &str) -> Self {
+        Self {
+            set_pipeline: name.to_string(),
+            file: file.to_string(),
+            vars: None,
+            var_files: None,
+            instance_vars: None,
+            team: None,
+            pipeline: None,
+        }
+    }
+
+    // Sets the current pipeline, i.e., `set_pipeline: self`.
+    pub fn self_(file: &str) -> Self {
+        Self::new("self", file)
+    }
+
+    pub fn with_vars<V: Into<Value> + Clone>(mut self, vars: &[(&str, V)]) -> Self {
+        self.vars = Some(to_map(vars));
+        self
+    }
+
+    pub fn with_var_files(mut self, var_files: &[&str]) -> Self {
+        self.var_files = Some(var_files.iter().map(|f| f.to_string()).collect());
+        self
+    }
+
+    pub fn with_instance_vars<V: Into<Value> + Clone>(
+        mut self,
+        instance_vars: &[(&str, V)],
+    ) -> Self {
+        self.instance_vars = Some(to_map(instance_vars));
+        self
+    }
+
+    pub fn with_team(mut self, team: &str) -> Self {
+        self.team = Some(team.to_string());
+        self
+    }
+
+    // Attaches a typed child pipeline to this step. The child pipeline will be
+    // cooked into 'file' by cook::cook_pipeline_files().
+    pub fn with_pipeline(mut self, pipeline: &Pipeline) -> Self {
+        self.pipeline = Some(Box::new(pipeline.clone()));
+        self
+    }
+
+    pub fn name(&self) -> Identifier {
+        self.set_pipeline.clone()
+    }
+
+    pub fn file(&self) -> FilePath {
+        self.file.clone()
+    }
+
+    pub fn to_step(self) -> Step {
+        Step::SetPipeline(self)
+    }
+}
@@ -0,0 +1,552 @@
+use crate::get::GetParams;
+use crate::get::PinnedVersion;
+use crate::resource::community;
+use crate::resource::community::GithubReleaseSource;
+use crate::resource::community::MockSource;
+use crate::resource::community::PoolSource;
+use crate::resource::community::S3Source;
+use crate::resource::community::SemverSource;
+use crate::resource::community::SlackNotificationSource;
+use crate::resource::Resource;
+use crate::resource::ResourceTypes;
+use crate::schema::boolean_is_false;
+use crate::schema::Config;
+use crate::schema::DirPath;
+use crate::schema::Duration;
+use crate::schema::Number;
+use crate::schema::Var;
+use crate::schema::Version;
+use git_url_parse::GitUrl;
+use serde::ser::Error;
+use serde::ser::SerializeMap;
+use serde::Serialize;
+use serde::Serializer;
+use std::collections::BTreeMap;
+
+// https://github.com/concourse/docker-image-resource#source-configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DockerImageSource {
+    pub(crate) repository: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) password: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) insecure_registries: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) registry_mirror: Option<String>,
+}
+
+impl DockerImageSource {
+    pub fn new(repository: &str) -> Self {
+        Self {
+            repository: repository.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.to_string());
+        self
+    }
+
+    pub fn with_credentials(mut self, username: &str, password: &Var) -> Self {
+        self.username = Some(username.to_string());
+        self.password = Some(password.to_string());
+        self
+    }
+
+    pub fn with_insecure_registries(mut self, insecure_registries: &[&str]) -> Self {
+        self.insecure_registries = insecure_registries.iter().map(|r| r.to_string()).collect();
+        self
+    }
+
+    pub fn with_registry_mirror(mut self, registry_mirror: &str) -> Self {
+        self.registry_mirror = Some(registry_mirror.to_string());
+        self
+    }
+
+    pub fn to_resource(self) -> Resource {
+        Resource::new(self.repository.as_str(), &ResourceTypes::DockerImage)
+            .with_icon("docker")
+            .with_typed_source(TypedSource::DockerImage(self))
+    }
+}
+
+// https://github.com/concourse/git-resource#source-configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GitSource {
+    pub(crate) uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) branch: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) paths: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) ignore_paths: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) private_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) tag_filter: Option<String>,
+    #[serde(skip_serializing_if = "boolean_is_false")]
+    pub(crate) fetch_tags: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) depth: Option<Number>,
+}
+
+impl GitSource {
+    pub fn new(uri: &str) -> Self {
+        GitUrl::parse(uri)
+            .unwrap_or_else(|_| panic!("The URI of given git resource '{}' is not valid", uri));
+        Self {
+            uri: uri.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_branch(mut self, branch: &str) -> Self {
+        self.branch = if branch.is_empty() {
+            None
+        } else {
+            Some(branch.to_string())
+        };
+        self
+    }
+
+    pub fn with_paths(mut self, paths: &[&str]) -> Self {
+        self.paths = paths.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    pub fn with_ignore_paths(mut self, ignore_paths: &[&str]) -> Self {
+        self.ignore_paths = ignore_paths.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    pub fn with_private_key(mut self, private_key: &Var) -> Self {
+        self.private_key = Some(private_key.to_string());
+        self
+    }
+
+    pub fn with_tag_filter(mut self, tag_filter: &str) -> Self {
+        self.tag_filter = Some(tag_filter.to_string());
+        self
+    }
+
+    pub fn with_fetch_tags(mut self, fetch_tags: bool) -> Self {
+        self.fetch_tags = fetch_tags;
+        self
+    }
+
+    pub fn with_depth(mut self, depth: Number) -> Self {
+        if depth < 1 {
+            panic!(
+                "The depth of git resource '{}' must be a positive number.",
+                self.uri
+            );
+        }
+        self.depth = Some(depth);
+        self
+    }
+
+    // The resource is named after the repository, suffixed with the branch if
+    // there is one, e.g., 'docs.main'.
+    pub fn to_resource(self) -> Resource {
+        let repository = GitUrl::parse(self.uri.as_str()).unwrap().name;
+        let name = match self.branch {
+            Some(ref branch) => format!("{}.{}", repository, branch),
+            None => repository,
+        };
+
+        let icon = if self.uri.contains("github") {
+            "github"
+        } else if self.uri.contains("gitlab") {
+            "gitlab"
+        } else {
+            ""
+        };
+
+        Resource::new(name.as_str(), &ResourceTypes::Git)
+            .with_icon(icon)
+            .with_typed_source(TypedSource::Git(self))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Submodules {
+    All,
+    None,
+    Paths(Vec<DirPath>),
+}
+
+impl Serialize for Submodules {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::All => serializer.serialize_str("all"),
+            Self::None => serializer.serialize_str("none"),
+            Self::Paths(ref paths) => paths.serialize(serializer),
+        }
+    }
+}
+
+// https://github.com/concourse/git-resource#in-clone-the-repository-at-the-given-ref
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GitGetParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) depth: Option<Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) submodules: Option<Submodules>,
+    #[serde(skip_serializing_if = "boolean_is_false")]
+    pub(crate) fetch_tags: bool,
+}
+
+impl GitGetParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_depth(mut self, depth: Number) -> Self {
+        if depth < 1 {
+            panic!("The depth of a git get step must be a positive number.");
+        }
+        self.depth = Some(depth);
+        self
+    }
+
+    pub fn with_submodules(mut self, submodules: Submodules) -> Self {
+        self.submodules = Some(submodules);
+        self
+    }
+
+    pub fn with_fetch_tags(mut self, fetch_tags: bool) -> Self {
+        self.fetch_tags = fetch_tags;
+        self
+    }
+}
+
+impl GetParams for GitGetParams {
+    fn resource_type(&self) -> ResourceTypes {
+        ResourceTypes::Git
+    }
+}
+
+// https://concourse-ci.org/resource-versions.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitVersion {
+    pub(crate) ref_: String,
+}
+
+impl GitVersion {
+    // The SHA of the commit to pin.
+    pub fn new(ref_: &str) -> Self {
+        Self {
+            ref_: ref_.to_string(),
+        }
+    }
+}
+
+impl PinnedVersion for GitVersion {
+    fn resource_type(&self) -> ResourceTypes {
+        ResourceTypes::Git
+    }
+
+    fn to_version(&self) -> Version {
+        Version::custom(&[("ref", self.ref_.as_str())])
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Platform {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) os: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) architecture: Option<String>,
+}
+
+// https://github.com/concourse/registry-image-resource#source-configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RegistryImageSource {
+    pub(crate) repository: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) platform: Option<Platform>,
+}
+
+impl RegistryImageSource {
+    pub fn new(repository: &str) -> Self {
+        Self {
+            repository: repository.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.to_string());
+        self
+    }
+
+    pub fn with_credentials(mut self, username: &str, password: &Var) -> Self {
+        self.username = Some(username.to_string());
+        self.password = Some(password.to_string());
+        self
+    }
+
+    // An empty os or architecture is left to the default of the worker.
+    pub fn with_platform(mut self, os: &str, architecture: &str) -> Self {
+        let some = |s: &str| {
+            if s.is_empty() {
+                None
+            } else {
+                Some(s.to_string())
+            }
+        };
+        self.platform = Some(Platform {
+            os: some(os),
+            architecture: some(architecture),
+        });
+        self
+    }
+
+    pub fn to_resource(self) -> Resource {
+        Resource::new(self.repository.as_str(), &ResourceTypes::RegistryImage)
+            .with_icon("docker")
+            .with_typed_source(TypedSource::RegistryImage(self))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImageFormat {
+    Rootfs,
+    Oci,
+    OciLayout,
+}
+
+// https://github.com/concourse/registry-image-resource#in-fetch-the-images-rootfs-and-metadata
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RegistryImageGetParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) format: Option<ImageFormat>,
+    #[serde(skip_serializing_if = "boolean_is_false")]
+    pub(crate) skip_download: bool,
+}
+
+impl RegistryImageGetParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_format(mut self, format: ImageFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    pub fn with_skip_download(mut self, skip_download: bool) -> Self {
+        self.skip_download = skip_download;
+        self
+    }
+}
+
+impl GetParams for RegistryImageGetParams {
+    fn resource_type(&self) -> ResourceTypes {
+        ResourceTypes::RegistryImage
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistryImageVersion {
+    pub(crate) digest: String,
+}
+
+impl RegistryImageVersion {
+    // The digest of the image to pin, e.g., 'sha256:...'.
+    pub fn new(digest: &str) -> Self {
+        Self {
+            digest: digest.to_string(),
+        }
+    }
+}
+
+impl PinnedVersion for RegistryImageVersion {
+    fn resource_type(&self) -> ResourceTypes {
+        ResourceTypes::RegistryImage
+    }
+
+    fn to_version(&self) -> Version {
+        Version::custom(&[("digest", self.digest.as_str())])
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+pub enum Day {
+    Sunday,
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+}
+
+// https://github.com/concourse/time-resource#source-configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TimeSource {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) interval: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) start: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) stop: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) location: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) days: Vec<Day>,
+}
+
+impl TimeSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    // Start and stop are times of the day, e.g., '8:00 PM' or '20:00'.
+    pub fn with_range(mut self, start: &str, stop: &str) -> Self {
+        self.start = Some(start.to_string());
+        self.stop = Some(stop.to_string());
+        self
+    }
+
+    pub fn with_location(mut self, location: &str) -> Self {
+        self.location = Some(location.to_string());
+        self
+    }
+
+    pub fn with_days(mut self, days: &[Day]) -> Self {
+        self.days = days.to_vec();
+        self
+    }
+
+    // The resource is named 'every-<interval>' when there is an interval,
+    // otherwise 'time'.
+    pub fn to_resource(self) -> Resource {
+        let name = match self.interval {
+            Some(ref interval) => format!("every-{}", interval),
+            None => String::from("time"),
+        };
+        Resource::new(name.as_str(), &ResourceTypes::Time)
+            .with_icon("clock-outline")
+            .with_typed_source(TypedSource::Time(self))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedSource {
+    DockerImage(DockerImageSource),
+    Git(GitSource),
+    RegistryImage(RegistryImageSource),
+    Time(TimeSource),
+    SlackNotification(SlackNotificationSource),
+    Semver(SemverSource),
+    S3(S3Source),
+    Pool(PoolSource),
+    GithubRelease(GithubReleaseSource),
+    Mock(MockSource),
+}
+
+impl Serialize for TypedSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::DockerImage(ref source) => source.serialize(serializer),
+            Self::Git(ref source) => source.serialize(serializer),
+            Self::RegistryImage(ref source) => source.serialize(serializer),
+            Self::Time(ref source) => source.serialize(serializer),
+            Self::SlackNotification(ref source) => source.serialize(serializer),
+            Self::Semver(ref source) => source.serialize(serializer),
+            Self::S3(ref source) => source.serialize(serializer),
+            Self::Pool(ref source) => source.serialize(serializer),
+            Self::GithubRelease(ref source) => source.serialize(serializer),
+            Self::Mock(ref source) => source.serialize(serializer),
+        }
+    }
+}
+
+impl TypedSource {
+    pub fn resource_type(&self) -> ResourceTypes {
+        match self {
+            Self::DockerImage(_) => ResourceTypes::DockerImage,
+            Self::Git(_) => ResourceTypes::Git,
+            Self::RegistryImage(_) => ResourceTypes::RegistryImage,
+            Self::Time(_) => ResourceTypes::Time,
+            Self::SlackNotification(_) => community::slack_notification_type(),
+            Self::Semver(_) => community::semver_type(),
+            Self::S3(_) => community::s3_type(),
+            Self::Pool(_) => community::pool_type(),
+            Self::GithubRelease(_) => community::github_release_type(),
+            Self::Mock(_) => community::mock_type(),
+        }
+    }
+}
+
+// The source of a resource. Keys set through Resource::with_source() are
+// merged on top of the typed source, so the raw map remains available for
+// fields that aren't typed (yet).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Source {
+    pub(crate) typed: Option<TypedSource>,
+    pub(crate) raw: Config,
+}
+
+impl Serialize for Source {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut fields = BTreeMap::new();
+        if let Some(ref typed) = self.typed {
+            match serde_yaml::to_value(typed).map_err(S::Error::custom)? {
+                serde_yaml::Value::Mapping(mapping) => {
+                    for (key, value) in mapping {
+                        match key {
+                            serde_yaml::Value::String(key) => fields.insert(key, value),
+                            _ => return Err(S::Error::custom("Source keys must be strings")),
+                        };
+                    }
+                }
+                _ => return Err(S::Error::custom("Typed sources must be mappings")),
+            }
+        }
+        for (key, value) in self.raw.iter() {
+            fields.insert(
+                key.clone(),
+                serde_yaml::to_value(value).map_err(S::Error::custom)?,
+            );
+        }
+
+        let mut state = serializer.serialize_map(Some(fields.len()))?;
+        for (key, value) in fields.iter() {
+            state.serialize_entry(key, value)?;
+        }
+        state.end()
+    }
+}
+
+impl Source {
+    pub(crate) fn raw(raw: Config) -> Self {
+        Self { typed: None, raw }
+    }
+
+    pub fn typed(&self) -> Option<&TypedSource> {
+        self.typed.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.typed.is_none() && self.raw.is_empty()
+    }
+}
@@ -1,6 +1,9 @@
 use crate::get::Get;
+use crate::load_var::LoadVar;
+use crate::modifier::ModifiedStep;
 use crate::put::Put;
 use crate::schema::Number;
+use crate::set_pipeline::SetPipeline;
 use crate::task::Task;
 use serde::ser::SerializeStruct;
 use serde::Serialize;
@@ -24,7 +27,64 @@ impl Serialize for InParallel {
                 state.serialize_field("in_parallel", steps)?;
                 state.end()
             }
-            InParallel::InParallelConfig { .. } => todo!(),
+            InParallel::InParallelConfig {
+                ref steps,
+                ref limit,
+                ref fail_fast,
+            } => {
+                let mut state = serializer.serialize_struct("InParallel", 1)?;
+                state.serialize_field(
+                    "in_parallel",
+                    &InParallelConfigRef {
+                        steps,
+                        limit,
+                        fail_fast,
+                    },
+                )?;
+                state.end()
+            }
+        }
+    }
+}
+
+struct InParallelConfigRef<'a> {
+    steps: &'a Vec<Step>,
+    limit: &'a Option<Number>,
+    fail_fast: &'a bool,
+}
+
+impl<'a> Serialize for InParallelConfigRef<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("InParallelConfig", 3)?;
+        state.serialize_field("steps", self.steps)?;
+        if let Some(limit) = self.limit {
+            state.serialize_field("limit", limit)?;
+        }
+        if *self.fail_fast {
+            state.serialize_field("fail_fast", self.fail_fast)?;
+        }
+        state.end()
+    }
+}
+
+impl InParallel {
+    pub fn steps(&self) -> &[Step] {
+        match self {
+            Self::Steps(ref steps) => steps,
+            Self::InParallelConfig { ref steps, .. } => steps,
+        }
+    }
+
+    pub(crate) fn with_steps(&self, steps: Vec<Step>) -> Self {
+        match self {
+            Self::Steps(_) => Self::Steps(steps),
+            Self::InParallelConfig {
+                limit, fail_fast, ..
+            } => Self::InParallelConfig {
+                steps,
+                limit: *limit,
+                fail_fast: *fail_fast,
+            },
         }
     }
 }
@@ -42,14 +102,41 @@ impl Serialize for Try {
     }
 }
 
+// Returns the step and every step nested in it, depth first. 'nested' returns
+// the steps directly nested in a step along with what the caller tracks for
+// them, e.g., their paths.
+pub(crate) fn walk_with<'a, T, F>(item: T, step: &'a Step, nested: &F) -> Vec<(T, &'a Step)>
+where
+    F: Fn(&T, &'a Step) -> Vec<(T, &'a Step)>,
+{
+    let sub_steps = nested(&item, step);
+    let mut steps = vec![(item, step)];
+    for (sub_item, sub_step) in sub_steps {
+        steps.append(&mut walk_with(sub_item, sub_step, nested));
+    }
+    steps
+}
+
+fn serialize_do<S: Serializer>(steps: &Vec<Step>, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut state = serializer.serialize_struct("Do", 1)?;
+    state.serialize_field("do", steps)?;
+    state.end()
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 pub enum Step {
     Get(Get),
     Put(Put),
     Task(Task),
     InParallel(InParallel),
     Try(Try),
+    SetPipeline(SetPipeline),
+    LoadVar(LoadVar),
+    #[serde(serialize_with = "serialize_do")]
+    Do(Vec<Step>),
+    Modified(ModifiedStep),
 }
 
 impl Step {
@@ -58,4 +145,33 @@ impl Step {
             try_: Box::new(step),
         })
     }
+
+    pub fn do_(steps: &[Step]) -> Self {
+        Self::Do(steps.to_vec())
+    }
+
+    // Returns steps that are directly nested in this step, including hooks.
+    pub(crate) fn sub_steps(&self) -> Vec<&Step> {
+        match self {
+            Self::Get(_) | Self::Put(_) | Self::SetPipeline(_) | Self::LoadVar(_) => vec![],
+            Self::Task(ref task) => task.hooks.steps(),
+            Self::InParallel(ref in_parallel) => in_parallel.steps().iter().collect(),
+            Self::Try(ref try_step) => vec![try_step.try_.as_ref()],
+            Self::Do(ref steps) => steps.iter().collect(),
+            Self::Modified(ref modified) => std::iter::once(modified.step())
+                .chain(modified.modifiers().hooks.steps())
+                .collect(),
+        }
+    }
+
+    // Returns the step and every step nested in it, depth first.
+    pub(crate) fn walk(&self) -> Vec<&Step> {
+        fn nested<'a>(_: &(), step: &'a Step) -> Vec<((), &'a Step)> {
+            step.sub_steps().into_iter().map(|s| ((), s)).collect()
+        }
+        walk_with((), self, &nested)
+            .into_iter()
+            .map(|(_, step)| step)
+            .collect()
+    }
 }
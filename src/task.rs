@@ -1,11 +1,17 @@
+use crate::modifier::Hooks;
 use crate::resource::Resource;
 use crate::resource::TaskImageResource;
+use crate::schema::boolean_is_false;
+use crate::schema::to_map;
+use crate::schema::ByteSize;
 use crate::schema::DirPath;
 use crate::schema::EnvVars;
 use crate::schema::FilePath;
 use crate::schema::Identifier;
+use crate::schema::Number;
+use crate::schema::Value;
 use crate::step::Step;
-use names::Generator;
+use serde::ser::Error;
 use serde::ser::SerializeStruct;
 use serde::Serialize;
 use serde::Serializer;
@@ -24,6 +30,10 @@ pub struct Command {
     path: FilePath,
     #[serde(skip_serializing_if = "Option::is_none")]
     args: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dir: Option<DirPath>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user: Option<String>,
 }
 
 impl Command {
@@ -35,12 +45,21 @@ impl Command {
             } else {
                 Some(args.iter().map(|s| s.to_string()).collect())
             },
+            dir: None,
+            user: None,
         }
     }
-}
 
-fn boolean_is_false(b: &bool) -> bool {
-    *b == false
+    // The working directory of the command, relative to the build directory.
+    pub fn with_dir(mut self, dir: &str) -> Self {
+        self.dir = Some(dir.to_string());
+        self
+    }
+
+    pub fn with_user(mut self, user: &str) -> Self {
+        self.user = Some(user.to_string());
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -65,6 +84,11 @@ impl Input {
         self.path = Some(path.to_string());
         self
     }
+
+    pub fn with_optional(mut self, optional: bool) -> Self {
+        self.optional = optional;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -88,27 +112,79 @@ impl Output {
     }
 }
 
+// https://concourse-ci.org/tasks.html#schema.cache
+#[derive(Debug, Clone, Serialize)]
+pub struct Cache {
+    pub(crate) path: DirPath,
+}
+
+// https://concourse-ci.org/tasks.html#schema.container_limits
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ContainerLimits {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) cpu: Option<Number>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) memory: Option<ByteSize>,
+}
+
+impl ContainerLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // CPU shares, relative to the other containers on the worker.
+    pub fn with_cpu(mut self, cpu: Number) -> Self {
+        if cpu < 1 {
+            panic!("The cpu limit of a container must be a positive number.");
+        }
+        self.cpu = Some(cpu);
+        self
+    }
+
+    pub fn with_memory(mut self, memory: ByteSize) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+}
+
+// Concourse requires exactly one of 'image_resource' and 'rootfs_uri'.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum TaskConfigImage {
+    ImageResource(TaskImageResource),
+    RootfsUri(String),
+}
+
 #[derive(Debug, Clone)]
 pub struct TaskConfig {
     pub(crate) platform: Platform,
-    pub(crate) image_resource: TaskImageResource,
+    pub(crate) image: TaskConfigImage,
     pub(crate) run: Command,
     pub(crate) params: Option<EnvVars>,
     pub(crate) inputs: Option<Vec<Input>>,
     pub(crate) outputs: Option<Vec<Output>>,
+    pub(crate) caches: Vec<Cache>,
+    pub(crate) container_limits: Option<ContainerLimits>,
     // When both the 'image' tag in 'task' struct and 'image_resource' tag in
     // the 'task_config' struct exist, we don't serialize the 'image_resource' tag,
-    // since the 'image' tag has higher priority.
+    // since the 'image' tag has higher priority. The same goes for 'rootfs_uri'.
     pub(crate) serialize_image_resource: bool,
 }
 
 impl Serialize for TaskConfig {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let mut state = serializer.serialize_struct("TaskConfig", 6)?;
+        let mut state = serializer.serialize_struct("TaskConfig", 8)?;
         state.serialize_field("platform", &self.platform)?;
         if self.serialize_image_resource {
-            let anonymouse_resource = self.image_resource.to_anonymouse_resource();
-            state.serialize_field("image_resource", &anonymouse_resource)?;
+            match self.image {
+                TaskConfigImage::ImageResource(ref image_resource) => {
+                    let anonymouse_resource = image_resource.to_anonymouse_resource();
+                    state.serialize_field("image_resource", &anonymouse_resource)?;
+                }
+                TaskConfigImage::RootfsUri(ref rootfs_uri) => {
+                    state.serialize_field("rootfs_uri", rootfs_uri)?;
+                }
+            }
         }
         state.serialize_field("run", &self.run)?;
         if self.params.is_some() {
@@ -120,6 +196,12 @@ impl Serialize for TaskConfig {
         if self.outputs.is_some() {
             state.serialize_field("outputs", &self.outputs)?;
         }
+        if !self.caches.is_empty() {
+            state.serialize_field("caches", &self.caches)?;
+        }
+        if let Some(ref container_limits) = self.container_limits {
+            state.serialize_field("container_limits", container_limits)?;
+        }
         state.end()
     }
 }
@@ -128,11 +210,15 @@ impl TaskConfig {
     pub fn linux_default() -> Self {
         Self {
             platform: Platform::Linux,
-            image_resource: Resource::registry_image("busybox").as_task_image_resource(),
-            run: Command::new("echo", &vec!["hello, world!"]),
+            image: TaskConfigImage::ImageResource(
+                Resource::registry_image("busybox").as_task_image_resource(),
+            ),
+            run: Command::new("echo", &["hello, world!"]),
             params: None,
             inputs: None,
             outputs: None,
+            caches: vec![],
+            container_limits: None,
             serialize_image_resource: true,
         }
     }
@@ -150,8 +236,15 @@ impl TaskConfig {
         self
     }
 
+    // Replaces the rootfs_uri, if any.
     pub fn with_image_resource(mut self, image_resource: &TaskImageResource) -> Self {
-        self.image_resource = image_resource.clone();
+        self.image = TaskConfigImage::ImageResource(image_resource.clone());
+        self
+    }
+
+    // Replaces the image_resource, e.g., the default busybox image.
+    pub fn with_rootfs_uri(mut self, rootfs_uri: &str) -> Self {
+        self.image = TaskConfigImage::RootfsUri(rootfs_uri.to_string());
         self
     }
 
@@ -160,12 +253,8 @@ impl TaskConfig {
         self
     }
 
-    pub fn with_env(mut self, env: &[(&str, &str)]) -> Self {
-        self.params = Some(
-            env.iter()
-                .map(|(k, v)| (k.to_string(), v.to_string()))
-                .collect(),
-        );
+    pub fn with_env<V: Into<Value> + Clone>(mut self, env: &[(&str, V)]) -> Self {
+        self.params = Some(to_map(env));
         self
     }
 
@@ -178,8 +267,25 @@ impl TaskConfig {
         self.outputs = Some(outputs);
         self
     }
+
+    pub fn with_container_limits(mut self, container_limits: ContainerLimits) -> Self {
+        self.container_limits = Some(container_limits);
+        self
+    }
+
+    // Caches are directories that are kept between builds of the same task.
+    pub fn with_caches(mut self, paths: &[&str]) -> Self {
+        self.caches = paths
+            .iter()
+            .map(|path| Cache {
+                path: path.to_string(),
+            })
+            .collect();
+        self
+    }
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
 pub enum TaskResource {
     Unbound,
@@ -263,6 +369,7 @@ impl TaskResource {
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub(crate) enum TaskDef {
     File { file: FilePath },
     Config { config: TaskConfig },
@@ -270,20 +377,17 @@ pub(crate) enum TaskDef {
 
 impl TaskDef {
     pub(crate) fn is_from_file(&self) -> bool {
-        match self {
-            Self::File { .. } => true,
-            _ => false,
-        }
+        matches!(self, Self::File { .. })
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Task {
-    task: Identifier,
+    pub(crate) task: Option<Identifier>,
     pub(crate) task_def: TaskDef,
     pub(crate) image: Option<TaskImageResource>,
-    priviledged: bool,
-    // TODO: container-limit.
+    privileged: bool,
+    container_limits: Option<ContainerLimits>,
     params: Option<EnvVars>,
     pub(crate) input_mapping: Option<BTreeMap<String, String>>,
     pub(crate) output_mapping: Option<BTreeMap<String, String>>,
@@ -292,17 +396,18 @@ pub struct Task {
     pub(crate) inputs: Option<Vec<TaskResource>>,
     // Outputs shouldn't be serialized!!
     pub(crate) outputs: Option<Vec<TaskResource>>,
-    // Hooks.
-    pub(crate) on_failure: Option<Box<Step>>,
-    pub(crate) on_abort: Option<Box<Step>>,
-    pub(crate) on_success: Option<Box<Step>>,
+    pub(crate) hooks: Hooks,
 }
 
 impl Serialize for Task {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let mut state = serializer.serialize_struct("Task", 9)?;
+        let mut state = serializer.serialize_struct("Task", 10)?;
 
-        state.serialize_field("task", &self.task)?;
+        // Unnamed tasks are named by cook, see cook::TaskNaming.
+        match self.task {
+            Some(ref name) => state.serialize_field("task", name)?,
+            None => return Err(S::Error::custom("Tasks must be named before serialization")),
+        }
 
         match self.task_def {
             TaskDef::Config { ref config, .. } => {
@@ -315,74 +420,76 @@ impl Serialize for Task {
             }
         }
 
-        if let Some(ref image) = self.image.as_ref() {
+        if let Some(image) = self.image.as_ref() {
             state.serialize_field("image", image.resource.name.as_str())?;
         }
 
-        if self.priviledged {
-            state.serialize_field("priviledged", &true)?;
+        if self.privileged {
+            state.serialize_field("privileged", &true)?;
         }
 
-        if let Some(ref input_mapping) = self.input_mapping.as_ref() {
-            state.serialize_field("input_mapping", input_mapping)?;
+        if let Some(ref container_limits) = self.container_limits {
+            state.serialize_field("container_limits", container_limits)?;
         }
 
-        if let Some(ref output_mapping) = self.output_mapping.as_ref() {
-            state.serialize_field("output_mapping", output_mapping)?;
+        if let Some(ref params) = self.params {
+            state.serialize_field("params", params)?;
         }
 
-        if let Some(ref on_failure) = self.on_failure.as_ref() {
-            state.serialize_field("on_failure", on_failure.as_ref())?;
+        if let Some(ref input_mapping) = self.input_mapping.as_ref() {
+            state.serialize_field("input_mapping", input_mapping)?;
         }
 
-        if let Some(ref on_abort) = self.on_abort.as_ref() {
-            state.serialize_field("on_abort", on_abort.as_ref())?;
+        if let Some(ref output_mapping) = self.output_mapping.as_ref() {
+            state.serialize_field("output_mapping", output_mapping)?;
         }
 
-        if let Some(ref on_success) = self.on_success.as_ref() {
-            state.serialize_field("on_success", on_success.as_ref())?;
-        }
+        self.hooks.serialize_fields(&mut state)?;
 
         state.end()
     }
 }
 
+impl Default for Task {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Task {
     pub fn new() -> Task {
         Self {
-            task: Generator::default().next().unwrap(),
+            task: None,
             task_def: TaskDef::Config {
                 config: TaskConfig::linux_default(),
             },
             image: None,
-            priviledged: false,
+            privileged: false,
+            container_limits: None,
             params: None,
             input_mapping: None,
             output_mapping: None,
             inputs: None,
             outputs: None,
-            on_abort: None,
-            on_failure: None,
-            on_success: None,
+            hooks: Hooks::default(),
         }
     }
 
     pub fn from_file(file: &str) -> Task {
         Self {
-            task: Generator::default().next().unwrap(),
+            task: None,
             task_def: TaskDef::File {
                 file: file.to_string(),
             },
             image: None,
-            priviledged: false,
+            privileged: false,
+            container_limits: None,
             params: None,
             input_mapping: None,
             output_mapping: None,
             inputs: None,
             outputs: None,
-            on_abort: None,
-            on_failure: None,
-            on_success: None,
+            hooks: Hooks::default(),
         }
     }
 
@@ -392,15 +499,23 @@ impl Task {
     }
 
     pub fn with_name(mut self, name: &str) -> Self {
-        self.task = name.to_string();
+        self.task = Some(name.to_string());
         self
     }
 
+    pub fn name(&self) -> Option<Identifier> {
+        self.task.clone()
+    }
+
+    fn label(&self) -> &str {
+        self.task.as_deref().unwrap_or("<unnamed>")
+    }
+
     pub fn run(mut self, command: &Command) -> Self {
         match self.task_def {
             TaskDef::File { .. } => panic!(
                 ".run() cannot be called in 'task' ('{}') that is initialized from 'file'.",
-                self.task.as_str()
+                self.label()
             ),
             TaskDef::Config { mut config } => {
                 config.run = command.clone();
@@ -410,6 +525,16 @@ impl Task {
         }
     }
 
+    pub fn with_privileged(mut self, privileged: bool) -> Self {
+        self.privileged = privileged;
+        self
+    }
+
+    pub fn with_container_limits(mut self, container_limits: ContainerLimits) -> Self {
+        self.container_limits = Some(container_limits);
+        self
+    }
+
     pub fn with_image(mut self, image: TaskImageResource) -> Self {
         self.image = Some(image);
         self
@@ -417,11 +542,11 @@ impl Task {
 
     pub fn with_image_resource(mut self, image_resource: TaskImageResource) -> Self {
         match self.task_def {
-            TaskDef::File { .. } => panic!(".with_image_resource() cannot be called in 'task' ('{}') that is initialized from 'file'.", self.task.as_str()),
+            TaskDef::File { .. } => panic!(".with_image_resource() cannot be called in 'task' ('{}') that is initialized from 'file'.", self.label()),
             TaskDef::Config {
                 mut config,
             } => {
-                config.image_resource = image_resource;
+                config.image = TaskConfigImage::ImageResource(image_resource);
                 self.task_def = TaskDef::Config {
                     config
                 };
@@ -430,13 +555,8 @@ impl Task {
         }
     }
 
-    pub fn with_params(mut self, params: &[(&str, &str)]) -> Self {
-        self.params = Some(
-            params
-                .iter()
-                .map(|(k, v)| (k.to_string(), v.to_string()))
-                .collect(),
-        );
+    pub fn with_params<V: Into<Value> + Clone>(mut self, params: &[(&str, V)]) -> Self {
+        self.params = Some(to_map(params));
         self
     }
 
@@ -444,7 +564,7 @@ impl Task {
         self.inputs = Some(
             inputs
                 .iter()
-                .map(|inp| inp.clone().clone())
+                .map(|inp| (*inp).clone())
                 .collect::<Vec<TaskResource>>(),
         );
 
@@ -471,13 +591,9 @@ impl Task {
                         ref name,
                         ref map_to,
                         ..
-                    } => {
-                        if let Some(ref map_to_name) = map_to {
-                            Some((map_to_name.clone(), name.clone()))
-                        } else {
-                            None
-                        }
-                    }
+                    } => map_to
+                        .as_ref()
+                        .map(|map_to_name| (map_to_name.clone(), name.clone())),
                     _ => {
                         panic!("Cannot pass unbound TaskResource to with_inputs()");
                     }
@@ -486,7 +602,7 @@ impl Task {
         } else {
             inputs
                 .iter()
-                .filter_map(|ref inp| match *inp {
+                .filter_map(|inp| match *inp {
                     TaskResource::Resource {
                         ref resource,
                         ref get_as,
@@ -506,14 +622,10 @@ impl Task {
                         ref name,
                         ref map_to,
                         ..
-                    } => {
-                        if let Some(ref map_to_name) = map_to {
-                            Some((map_to_name.clone(), name.clone()))
-                        } else {
-                            None
-                        }
-                    }
-                    TaskResource::Unbound { .. } => {
+                    } => map_to
+                        .as_ref()
+                        .map(|map_to_name| (map_to_name.clone(), name.clone())),
+                    TaskResource::Unbound => {
                         panic!("Cannot pass unbound TaskResource to with_inputs()");
                     }
                 })
@@ -543,7 +655,7 @@ impl Task {
                         if let Some(ref map_from_name) = map_from {
                             output_mapping.insert(map_from_name.clone(), name.clone());
                         }
-                        v.clone().clone()
+                        (*v).clone()
                     }
                     _ => panic!("Only 'Output' TaskResource can be used in with_outputs()"),
                 })
@@ -563,7 +675,7 @@ impl Task {
         task_config_mutator: F,
     ) -> Self {
         match self.task_def {
-            TaskDef::File { .. } => panic!(".mutate_task_config() cannot be called in 'task' ('{}') that is initialized from 'file'.", self.task.as_str()),
+            TaskDef::File { .. } => panic!(".mutate_task_config() cannot be called in 'task' ('{}') that is initialized from 'file'.", self.label()),
             TaskDef::Config {
                 config,
             } => {
@@ -580,17 +692,32 @@ impl Task {
     }
 
     pub fn on_failure(mut self, step: Step) -> Self {
-        self.on_failure = Some(Box::new(step));
+        self.hooks.on_failure = Some(Box::new(step));
+        self
+    }
+
+    pub fn on_error(mut self, step: Step) -> Self {
+        self.hooks.on_error = Some(Box::new(step));
         self
     }
 
     pub fn on_abort(mut self, step: Step) -> Self {
-        self.on_abort = Some(Box::new(step));
+        self.hooks.on_abort = Some(Box::new(step));
         self
     }
 
     pub fn on_success(mut self, step: Step) -> Self {
-        self.on_success = Some(Box::new(step));
+        self.hooks.on_success = Some(Box::new(step));
+        self
+    }
+
+    pub fn ensure(mut self, step: Step) -> Self {
+        self.hooks.ensure = Some(Box::new(step));
+        self
+    }
+
+    pub(crate) fn without_hooks(mut self) -> Self {
+        self.hooks = Hooks::default();
         self
     }
 }
@@ -0,0 +1,919 @@
+use crate::cook::collect_definition;
+use crate::cook::collect_type_definition;
+use crate::cook::name_tasks;
+use crate::cook::CatchAllGroup;
+use crate::cook::CookOptions;
+use crate::errors::Errors;
+use crate::job::Job;
+use crate::pipeline::Pipeline;
+use crate::resource::Resource;
+use crate::resource::ResourceTypes;
+use crate::schema::Identifier;
+use crate::schema::Number;
+use crate::schema::Var;
+use crate::step::walk_with;
+use crate::step::Step;
+use crate::task::Task;
+use crate::task::TaskDef;
+use crate::task::TaskResource;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    DuplicateJob {
+        job: Identifier,
+    },
+    NonPositiveMaxInFlight {
+        job: Identifier,
+    },
+    SerialWithMaxInFlight {
+        job: Identifier,
+        max_in_flight: Number,
+    },
+    NegativeBuildLogRetention {
+        job: Identifier,
+        option: String,
+    },
+    MinimumSucceededBuildsAboveBuilds {
+        job: Identifier,
+    },
+    DuplicateGroup {
+        group: Identifier,
+    },
+    UnknownGroupJob {
+        group: Identifier,
+        job: Identifier,
+    },
+    // A group named like the catch-all group of CookOptions.
+    CatchAllGroupConflict {
+        group: Identifier,
+    },
+    GeneratedTaskNameCollision {
+        task: Identifier,
+    },
+    // Resources are collected from the steps using them, so every step must
+    // agree on the definition, unless cook renames them, see ResourceDedup.
+    ConflictingResource {
+        resource: Identifier,
+        first: String,
+        second: String,
+    },
+    ConflictingResourceType {
+        resource_type: Identifier,
+        first: String,
+        second: String,
+    },
+    UnknownPassedJob {
+        resource: Identifier,
+        job: Identifier,
+    },
+    PassedJobWithoutResource {
+        resource: Identifier,
+        job: Identifier,
+    },
+    // A task input that no preceding get, put or task output provides.
+    UnproducedTaskInput {
+        input: Identifier,
+    },
+    // Local vars can only be used after the load_var step that defines them
+    // or inside the step whose across modifier defines them.
+    UndefinedLocalVar {
+        var: String,
+    },
+    DuplicateVarSource {
+        var_source: Identifier,
+    },
+    UndeclaredVarSource {
+        var: String,
+        var_source: Identifier,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateJob { job } => write!(f, "Job '{}' is defined more than once", job),
+            Self::NonPositiveMaxInFlight { job } => write!(
+                f,
+                "The max_in_flight of job '{}' must be a positive number",
+                job
+            ),
+            Self::SerialWithMaxInFlight { job, max_in_flight } => write!(
+                f,
+                "Job '{}' cannot be serial with max_in_flight {}",
+                job, max_in_flight
+            ),
+            Self::NegativeBuildLogRetention { job, option } => write!(
+                f,
+                "The build_log_retention.{} of job '{}' cannot be negative",
+                option, job
+            ),
+            Self::MinimumSucceededBuildsAboveBuilds { job } => write!(
+                f,
+                "The build_log_retention.minimum_succeeded_builds of job '{}' cannot be larger than build_log_retention.builds",
+                job
+            ),
+            Self::DuplicateGroup { group } => {
+                write!(f, "Group '{}' is defined more than once", group)
+            }
+            Self::UnknownGroupJob { group, job } => write!(
+                f,
+                "Group '{}' references job '{}' that is not in the pipeline",
+                group, job
+            ),
+            Self::CatchAllGroupConflict { group } => {
+                write!(f, "Group '{}' conflicts with the catch-all group", group)
+            }
+            Self::GeneratedTaskNameCollision { task } => write!(
+                f,
+                "Generated task name '{}' collides with another task, name the tasks explicitly",
+                task
+            ),
+            Self::ConflictingResource {
+                resource,
+                first,
+                second,
+            } => write!(
+                f,
+                "Resource '{}' has conflicting definitions:\n{}---\n{}",
+                resource, first, second
+            ),
+            Self::ConflictingResourceType {
+                resource_type,
+                first,
+                second,
+            } => write!(
+                f,
+                "Resource type '{}' has conflicting definitions:\n{}---\n{}",
+                resource_type, first, second
+            ),
+            Self::UnknownPassedJob { resource, job } => write!(
+                f,
+                "Resource '{}' must pass job '{}' that is not in the pipeline",
+                resource, job
+            ),
+            Self::PassedJobWithoutResource { resource, job } => write!(
+                f,
+                "Resource '{}' must pass job '{}' that never gets or puts it",
+                resource, job
+            ),
+            Self::UnproducedTaskInput { input } => {
+                write!(f, "Input '{}' is not produced by any preceding step", input)
+            }
+            Self::UndefinedLocalVar { var } => write!(
+                f,
+                "Local var '{}' is not defined by a preceding load_var step or an enclosing across step",
+                var
+            ),
+            Self::DuplicateVarSource { var_source } => {
+                write!(f, "Var source '{}' is declared more than once", var_source)
+            }
+            Self::UndeclaredVarSource { var, var_source } => write!(
+                f,
+                "Var '{}' refers to var source '{}' that is not declared in the pipeline",
+                var, var_source
+            ),
+        }
+    }
+}
+
+// A problem and where it was found, e.g., 'jobs[unit].plan[2].task[build]'.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub(crate) path: String,
+    pub(crate) problem: Problem,
+}
+
+impl Diagnostic {
+    pub(crate) fn new(path: &str, problem: Problem) -> Self {
+        Self {
+            path: path.to_string(),
+            problem,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    pub fn problem(&self) -> &Problem {
+        &self.problem
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.problem)
+    }
+}
+
+// Name of the artifact a get or put step makes available to following steps.
+fn artifact_name(alias: &str, resource: &Resource) -> Identifier {
+    if alias.is_empty() {
+        resource.name()
+    } else {
+        alias.to_string()
+    }
+}
+
+fn label(step: &Step) -> String {
+    match step {
+        Step::Get(ref get_step) => {
+            format!("get[{}]", artifact_name(&get_step.get, &get_step.resource))
+        }
+        Step::Put(ref put_step) => {
+            format!("put[{}]", artifact_name(&put_step.put, &put_step.resource))
+        }
+        Step::Task(ref task) => match task.name() {
+            Some(name) => format!("task[{}]", name),
+            None => "task".to_string(),
+        },
+        Step::SetPipeline(ref set_pipeline) => format!("set_pipeline[{}]", set_pipeline.name()),
+        Step::LoadVar(ref load_var) => format!("load_var[{}]", load_var.name()),
+        Step::InParallel(_) => "in_parallel".to_string(),
+        Step::Try(_) => "try".to_string(),
+        Step::Do(_) => "do".to_string(),
+        // Modifiers don't show up in the path, the modified step does.
+        Step::Modified(ref modified) => label(modified.step()),
+    }
+}
+
+// Steps nested in 'step' with their paths.
+fn children<'a>(path: &str, step: &'a Step) -> Vec<(String, &'a Step)> {
+    let indexed = |steps: &'a [Step]| {
+        steps
+            .iter()
+            .enumerate()
+            .map(|(i, sub_step)| (format!("{}[{}].{}", path, i, label(sub_step)), sub_step))
+            .collect::<Vec<_>>()
+    };
+    let hooks = |hooks: &'a crate::modifier::Hooks| {
+        hooks
+            .named()
+            .into_iter()
+            .filter_map(|(name, hook)| {
+                hook.as_deref()
+                    .map(|hook| (format!("{}.{}.{}", path, name, label(hook)), hook))
+            })
+            .collect::<Vec<_>>()
+    };
+    match step {
+        Step::Get(_) | Step::Put(_) | Step::SetPipeline(_) | Step::LoadVar(_) => vec![],
+        Step::Task(ref task) => hooks(&task.hooks),
+        Step::InParallel(ref in_parallel) => indexed(in_parallel.steps()),
+        Step::Do(ref steps) => indexed(steps),
+        Step::Try(ref try_step) => {
+            let sub_step = try_step.try_.as_ref();
+            vec![(format!("{}.{}", path, label(sub_step)), sub_step)]
+        }
+        Step::Modified(ref modified) => {
+            let mut steps = children(path, modified.step());
+            steps.append(&mut hooks(&modified.modifiers().hooks));
+            steps
+        }
+    }
+}
+
+// Top level steps of the job, i.e., the plan followed by the job hooks.
+fn job_steps(job: &Job) -> Vec<(String, &Step)> {
+    let prefix = format!("jobs[{}]", job.name);
+    let mut steps = job
+        .plan
+        .iter()
+        .enumerate()
+        .map(|(i, step)| (format!("{}.plan[{}].{}", prefix, i, label(step)), step))
+        .collect::<Vec<_>>();
+    for (name, hook) in job.hooks.named() {
+        if let Some(hook) = hook.as_deref() {
+            steps.push((format!("{}.{}.{}", prefix, name, label(hook)), hook));
+        }
+    }
+    steps
+}
+
+fn unmodified(step: &Step) -> &Step {
+    match step {
+        Step::Modified(ref modified) => unmodified(modified.step()),
+        _ => step,
+    }
+}
+
+// Resources used by the step, including those cook gets implicitly for tasks.
+fn resources_of(step: &Step) -> Vec<&Resource> {
+    match unmodified(step) {
+        Step::Get(ref get_step) => vec![&get_step.resource],
+        Step::Put(ref put_step) => vec![&put_step.resource],
+        Step::Task(ref task) => task
+            .inputs
+            .iter()
+            .flatten()
+            .filter_map(|input| match input {
+                TaskResource::Resource { ref resource, .. } => Some(resource),
+                _ => None,
+            })
+            .chain(task.image.iter().map(|image| &image.resource))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+// Combinations of job options that Concourse rejects.
+fn check_job_config(job: &Job, diagnostics: &mut Vec<Diagnostic>) {
+    let mut problems = vec![];
+    if let Some(max_in_flight) = job.max_in_flight {
+        if max_in_flight < 1 {
+            problems.push(Problem::NonPositiveMaxInFlight { job: job.name() });
+        } else if max_in_flight > 1 && job.serial == Some(true) {
+            problems.push(Problem::SerialWithMaxInFlight {
+                job: job.name(),
+                max_in_flight,
+            });
+        }
+    }
+
+    if let Some(ref retention) = job.build_log_retention {
+        for (option, value) in [
+            ("builds", retention.builds),
+            ("days", retention.days),
+            (
+                "minimum_succeeded_builds",
+                retention.minimum_succeeded_builds,
+            ),
+        ] {
+            if value.unwrap_or(0) < 0 {
+                problems.push(Problem::NegativeBuildLogRetention {
+                    job: job.name(),
+                    option: option.to_string(),
+                });
+            }
+        }
+        if let (Some(builds), Some(minimum_succeeded_builds)) =
+            (retention.builds, retention.minimum_succeeded_builds)
+        {
+            if minimum_succeeded_builds > builds {
+                problems.push(Problem::MinimumSucceededBuildsAboveBuilds { job: job.name() });
+            }
+        }
+    }
+
+    let path = format!("jobs[{}]", job.name);
+    diagnostics.extend(
+        problems
+            .into_iter()
+            .map(|problem| Diagnostic::new(&path, problem)),
+    );
+}
+
+fn check_jobs(pipeline: &Pipeline, diagnostics: &mut Vec<Diagnostic>) {
+    let mut names = BTreeSet::new();
+    for job in pipeline.jobs.iter() {
+        if !names.insert(job.name()) {
+            diagnostics.push(Diagnostic::new(
+                format!("jobs[{}]", job.name).as_str(),
+                Problem::DuplicateJob { job: job.name() },
+            ));
+        }
+        check_job_config(job, diagnostics);
+    }
+}
+
+fn check_groups(pipeline: &Pipeline, options: &CookOptions, diagnostics: &mut Vec<Diagnostic>) {
+    let jobs = pipeline
+        .jobs
+        .iter()
+        .map(|job| job.name())
+        .collect::<BTreeSet<_>>();
+    let catch_all = match options.catch_all_group {
+        CatchAllGroup::None => None,
+        CatchAllGroup::All => Some("all"),
+        CatchAllGroup::Ungrouped => Some("ungrouped"),
+    };
+    let mut names = BTreeSet::new();
+    for group in pipeline.groups.iter() {
+        let path = format!("groups[{}]", group.name);
+        if !names.insert(group.name.as_str()) {
+            diagnostics.push(Diagnostic::new(
+                &path,
+                Problem::DuplicateGroup {
+                    group: group.name.clone(),
+                },
+            ));
+        }
+        if catch_all == Some(group.name.as_str()) {
+            diagnostics.push(Diagnostic::new(
+                &path,
+                Problem::CatchAllGroupConflict {
+                    group: group.name.clone(),
+                },
+            ));
+        }
+        for job in group.jobs.iter().filter(|job| !jobs.contains(*job)) {
+            diagnostics.push(Diagnostic::new(
+                &path,
+                Problem::UnknownGroupJob {
+                    group: group.name.clone(),
+                    job: job.clone(),
+                },
+            ));
+        }
+    }
+}
+
+// A step with its path and the names of its resources as cook collects them.
+struct CollectedStep<'a> {
+    path: String,
+    step: &'a Step,
+    resources: Vec<Identifier>,
+}
+
+// Collects the resources and types the same way cook does, so conflicts are
+// found by the same comparison and renamed resources get the same names.
+#[derive(Default)]
+struct Collector {
+    resources: BTreeMap<String, Resource>,
+    resource_types: BTreeMap<String, ResourceTypes>,
+    // Places using the same conflicting definition are reported once.
+    reported: BTreeSet<(String, String)>,
+    reported_types: BTreeSet<(String, String)>,
+}
+
+impl Collector {
+    fn collect_type(
+        &mut self,
+        path: &str,
+        resource_type: &ResourceTypes,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        if let Err(Errors::ConflictingResourceTypes {
+            name,
+            first,
+            second,
+        }) = collect_type_definition(resource_type, &mut self.resource_types)
+        {
+            if self.reported_types.insert((name.clone(), second.clone())) {
+                diagnostics.push(Diagnostic::new(
+                    path,
+                    Problem::ConflictingResourceType {
+                        resource_type: name,
+                        first,
+                        second,
+                    },
+                ));
+            }
+        }
+    }
+
+    // Returns the name the resource is collected under.
+    fn collect(
+        &mut self,
+        path: &str,
+        resource: &Resource,
+        options: &CookOptions,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Option<Identifier> {
+        self.collect_type(path, &resource.type_, diagnostics);
+        match collect_definition(resource, &mut self.resources, options) {
+            Ok(collected) => Some(collected.name()),
+            Err(Errors::ConflictingResources {
+                name,
+                first,
+                second,
+            }) => {
+                if self.reported.insert((name.clone(), second.clone())) {
+                    diagnostics.push(Diagnostic::new(
+                        path,
+                        Problem::ConflictingResource {
+                            resource: name,
+                            first,
+                            second,
+                        },
+                    ));
+                }
+                None
+            }
+            // Serde errors are reported by cook.
+            Err(_) => None,
+        }
+    }
+}
+
+// Declared resources and types are collected before those of the steps, as
+// cook does.
+fn check_resources(
+    pipeline: &Pipeline,
+    steps_of_jobs: &mut [(Identifier, Vec<CollectedStep>)],
+    options: &CookOptions,
+    collector: &mut Collector,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for resource in pipeline.resources.iter() {
+        let path = format!("resources[{}]", resource.name);
+        collector.collect(&path, resource, options, diagnostics);
+    }
+    for resource_type in pipeline.resource_types.iter() {
+        let path = format!("resource_types[{}]", resource_type);
+        collector.collect_type(&path, resource_type, diagnostics);
+    }
+
+    for collected_step in steps_of_jobs
+        .iter_mut()
+        .flat_map(|(_, steps)| steps.iter_mut())
+    {
+        for (i, resource) in resources_of(collected_step.step).into_iter().enumerate() {
+            if let Some(name) =
+                collector.collect(&collected_step.path, resource, options, diagnostics)
+            {
+                collected_step.resources[i] = name;
+            }
+        }
+    }
+}
+
+// Jobs in 'passed' must exist and get or put the same resource, otherwise no
+// version can ever satisfy the constraint.
+fn check_passed(
+    steps_of_jobs: &[(Identifier, Vec<CollectedStep>)],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut resources_of_jobs: BTreeMap<&str, BTreeSet<&Identifier>> = BTreeMap::new();
+    for (job, steps) in steps_of_jobs.iter() {
+        resources_of_jobs
+            .entry(job.as_str())
+            .or_default()
+            .extend(steps.iter().flat_map(|step| step.resources.iter()));
+    }
+
+    for collected_step in steps_of_jobs.iter().flat_map(|(_, steps)| steps.iter()) {
+        if let Step::Get(ref get_step) = unmodified(collected_step.step) {
+            // Passed jobs must use the resource under the name cook gives it.
+            let resource = &collected_step.resources[0];
+            for passed in get_step.passed.iter() {
+                let problem = match resources_of_jobs.get(passed.as_str()) {
+                    None => Problem::UnknownPassedJob {
+                        resource: resource.clone(),
+                        job: passed.clone(),
+                    },
+                    Some(resources) if !resources.contains(resource) => {
+                        Problem::PassedJobWithoutResource {
+                            resource: resource.clone(),
+                            job: passed.clone(),
+                        }
+                    }
+                    _ => continue,
+                };
+                diagnostics.push(Diagnostic::new(&collected_step.path, problem));
+            }
+        }
+    }
+}
+
+fn check_task_inputs(
+    task: &Task,
+    path: &str,
+    available: &mut BTreeSet<Identifier>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut required = vec![];
+    for input in task.inputs.iter().flatten() {
+        match input {
+            // Cook gets these resources before the task.
+            TaskResource::Resource {
+                ref resource,
+                ref get_as,
+                ..
+            } => {
+                available.insert(get_as.clone().unwrap_or_else(|| resource.name()));
+            }
+            TaskResource::Output { ref name, .. } => required.push(name.clone()),
+            TaskResource::Unbound => { /* Do nothing. */ }
+        }
+    }
+    if let Some(ref image) = task.image {
+        available.insert(image.resource.name());
+    }
+
+    let mut produced = vec![];
+    if let TaskDef::Config { ref config } = task.task_def {
+        let input_mapping = task.input_mapping.clone().unwrap_or_default();
+        for input in config
+            .inputs
+            .iter()
+            .flatten()
+            .filter(|input| !input.optional)
+        {
+            required.push(
+                input_mapping
+                    .get(&input.name)
+                    .cloned()
+                    .unwrap_or_else(|| input.name.clone()),
+            );
+        }
+        let output_mapping = task.output_mapping.clone().unwrap_or_default();
+        for output in config.outputs.iter().flatten() {
+            produced.push(
+                output_mapping
+                    .get(&output.name)
+                    .cloned()
+                    .unwrap_or_else(|| output.name.clone()),
+            );
+        }
+    }
+    for output in task.outputs.iter().flatten() {
+        if let TaskResource::Output { ref name, .. } = output {
+            produced.push(name.clone());
+        }
+    }
+
+    let mut reported = BTreeSet::new();
+    for input in required {
+        if !available.contains(&input) && reported.insert(input.clone()) {
+            diagnostics.push(Diagnostic::new(
+                path,
+                Problem::UnproducedTaskInput { input },
+            ));
+        }
+    }
+    available.extend(produced);
+}
+
+// Follows the artifacts that each step makes available to the steps after it.
+fn check_inputs_in_step(
+    path: &str,
+    step: &Step,
+    available: &mut BTreeSet<Identifier>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let sub_steps = children(path, step);
+    match step {
+        Step::Get(ref get_step) => {
+            available.insert(artifact_name(&get_step.get, &get_step.resource));
+        }
+        Step::Put(ref put_step) => {
+            if !put_step.no_get {
+                available.insert(artifact_name(&put_step.put, &put_step.resource));
+            }
+        }
+        Step::SetPipeline(_) | Step::LoadVar(_) => { /* Do nothing. */ }
+        Step::Task(ref task) => {
+            check_task_inputs(task, path, available, diagnostics);
+            // Artifacts of hooks are not available to the following steps.
+            for (sub_path, sub_step) in sub_steps {
+                check_inputs_in_step(&sub_path, sub_step, &mut available.clone(), diagnostics);
+            }
+        }
+        Step::InParallel(_) => {
+            // Parallel steps cannot use artifacts of their siblings.
+            let mut produced = BTreeSet::new();
+            for (sub_path, sub_step) in sub_steps {
+                let mut scope = available.clone();
+                check_inputs_in_step(&sub_path, sub_step, &mut scope, diagnostics);
+                produced.append(&mut scope);
+            }
+            available.append(&mut produced);
+        }
+        Step::Try(_) | Step::Do(_) => {
+            for (sub_path, sub_step) in sub_steps {
+                check_inputs_in_step(&sub_path, sub_step, available, diagnostics);
+            }
+        }
+        Step::Modified(ref modified) => {
+            check_inputs_in_step(path, modified.step(), available, diagnostics);
+            let nested = children(path, modified.step()).len();
+            for (sub_path, sub_step) in sub_steps.into_iter().skip(nested) {
+                check_inputs_in_step(&sub_path, sub_step, &mut available.clone(), diagnostics);
+            }
+        }
+    }
+}
+
+fn check_inputs(job: &Job, diagnostics: &mut Vec<Diagnostic>) {
+    let mut available = BTreeSet::new();
+    let steps = job_steps(job);
+    let plan = job.plan.len();
+    for (path, step) in steps.iter().take(plan) {
+        check_inputs_in_step(path, step, &mut available, diagnostics);
+    }
+    for (path, step) in steps.iter().skip(plan) {
+        check_inputs_in_step(path, step, &mut available.clone(), diagnostics);
+    }
+}
+
+// Vars used by the step itself, leaving out the steps nested in it.
+fn vars_of(step: &Step) -> Vec<Var> {
+    let vars = match step {
+        Step::InParallel(_) | Step::Try(_) | Step::Do(_) => Ok(vec![]),
+        Step::Task(ref task) => Var::find_all_in(&task.clone().without_hooks()),
+        Step::Modified(ref modified) => Var::find_all_in(&modified.modifiers().without_hooks())
+            .map(|mut vars| {
+                vars.append(&mut vars_of(modified.step()));
+                vars
+            }),
+        _ => Var::find_all_in(step),
+    };
+    // Serde errors are reported by cook.
+    vars.unwrap_or_default()
+}
+
+// Follows the local vars that each step defines for the steps after it.
+fn check_local_vars_in_step(
+    path: &str,
+    step: &Step,
+    defined: &mut BTreeSet<String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let used = match step {
+        // The modified step is checked on its own, within the across scope.
+        Step::Modified(ref modified) => {
+            Var::find_all_in(&modified.modifiers().without_hooks()).unwrap_or_default()
+        }
+        _ => vars_of(step),
+    };
+    let mut reported = BTreeSet::new();
+    for var in used.iter().filter(|var| var.is_local()) {
+        if !defined.contains(&var.path) && reported.insert(var.to_string()) {
+            diagnostics.push(Diagnostic::new(
+                path,
+                Problem::UndefinedLocalVar {
+                    var: var.to_string(),
+                },
+            ));
+        }
+    }
+
+    let sub_steps = children(path, step);
+    match step {
+        Step::LoadVar(ref load_var) => {
+            defined.insert(load_var.name());
+        }
+        Step::Modified(ref modified) => {
+            // Across vars are only visible to the modified step and its hooks.
+            let mut scope = defined.clone();
+            scope.extend(modified.modifiers().across.iter().map(|var| var.name()));
+            check_local_vars_in_step(path, modified.step(), &mut scope, diagnostics);
+            let nested = children(path, modified.step()).len();
+            for (sub_path, sub_step) in sub_steps.into_iter().skip(nested) {
+                check_local_vars_in_step(&sub_path, sub_step, &mut scope.clone(), diagnostics);
+            }
+        }
+        Step::InParallel(_) => {
+            // Parallel steps cannot see vars loaded by their siblings.
+            let mut loaded = BTreeSet::new();
+            for (sub_path, sub_step) in sub_steps {
+                let mut scope = defined.clone();
+                check_local_vars_in_step(&sub_path, sub_step, &mut scope, diagnostics);
+                loaded.append(&mut scope);
+            }
+            defined.append(&mut loaded);
+        }
+        Step::Task(_) => {
+            // Hooks only run on some outcomes, so vars they load are not
+            // visible to the steps after the task.
+            for (sub_path, sub_step) in sub_steps {
+                check_local_vars_in_step(&sub_path, sub_step, &mut defined.clone(), diagnostics);
+            }
+        }
+        _ => {
+            for (sub_path, sub_step) in sub_steps {
+                check_local_vars_in_step(&sub_path, sub_step, defined, diagnostics);
+            }
+        }
+    }
+}
+
+fn check_local_vars(job: &Job, diagnostics: &mut Vec<Diagnostic>) {
+    let mut defined = BTreeSet::new();
+    let steps = job_steps(job);
+    let plan = job.plan.len();
+    for (path, step) in steps.iter().take(plan) {
+        check_local_vars_in_step(path, step, &mut defined, diagnostics);
+    }
+    for (path, step) in steps.iter().skip(plan) {
+        check_local_vars_in_step(path, step, &mut defined.clone(), diagnostics);
+    }
+}
+
+// Vars with a source prefix, e.g., '((vault:path))' must refer to a declared
+// var source.
+fn check_var_sources(
+    pipeline: &Pipeline,
+    steps_of_jobs: &[(Identifier, Vec<CollectedStep>)],
+    resource_collector: &BTreeMap<String, Resource>,
+    type_collector: &BTreeMap<String, ResourceTypes>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut declared = BTreeSet::new();
+    let mut users = vec![];
+    for var_source in pipeline.var_sources.iter() {
+        let path = format!("var_sources[{}]", var_source.name());
+        if !declared.insert(var_source.name()) {
+            diagnostics.push(Diagnostic::new(
+                &path,
+                Problem::DuplicateVarSource {
+                    var_source: var_source.name(),
+                },
+            ));
+        }
+        users.push((path, Var::find_all_in(var_source).unwrap_or_default()));
+    }
+    for collected_step in steps_of_jobs.iter().flat_map(|(_, steps)| steps.iter()) {
+        users.push((collected_step.path.clone(), vars_of(collected_step.step)));
+    }
+    for (name, resource) in resource_collector.iter() {
+        users.push((
+            format!("resources[{}]", name),
+            Var::find_all_in(resource).unwrap_or_default(),
+        ));
+    }
+    for (name, resource_type) in type_collector.iter() {
+        users.push((
+            format!("resource_types[{}]", name),
+            Var::find_all_in(resource_type).unwrap_or_default(),
+        ));
+    }
+
+    for (path, vars) in users {
+        let mut reported = BTreeSet::new();
+        for var in vars {
+            if let Some(ref source) = var.source {
+                if !var.is_local() && !declared.contains(source) && reported.insert(var.to_string())
+                {
+                    diagnostics.push(Diagnostic::new(
+                        &path,
+                        Problem::UndeclaredVarSource {
+                            var: var.to_string(),
+                            var_source: source.clone(),
+                        },
+                    ));
+                }
+            }
+        }
+    }
+}
+
+// Names the tasks the way cook names them, so paths match, and checks the
+// named pipeline. Cook continues with the returned pipeline if there are no
+// diagnostics.
+pub(crate) fn diagnose(
+    pipeline: &Pipeline,
+    options: &CookOptions,
+) -> Result<(Pipeline, Vec<Diagnostic>), Errors> {
+    let mut diagnostics = vec![];
+    let pipeline = name_tasks(pipeline, options, &mut diagnostics)?;
+    check_jobs(&pipeline, &mut diagnostics);
+    check_groups(&pipeline, options, &mut diagnostics);
+
+    let mut steps_of_jobs = pipeline
+        .jobs
+        .iter()
+        .map(|job| {
+            let nested = |path: &String, step| children(path, step);
+            let steps = job_steps(job)
+                .into_iter()
+                .flat_map(|(path, step)| walk_with(path, step, &nested))
+                .map(|(path, step)| CollectedStep {
+                    path,
+                    step,
+                    resources: resources_of(step).iter().map(|r| r.name()).collect(),
+                })
+                .collect::<Vec<_>>();
+            (job.name(), steps)
+        })
+        .collect::<Vec<_>>();
+    let mut collector = Collector::default();
+    check_resources(
+        &pipeline,
+        &mut steps_of_jobs,
+        options,
+        &mut collector,
+        &mut diagnostics,
+    );
+    check_passed(&steps_of_jobs, &mut diagnostics);
+
+    for job in pipeline.jobs.iter() {
+        check_inputs(job, &mut diagnostics);
+        check_local_vars(job, &mut diagnostics);
+    }
+    check_var_sources(
+        &pipeline,
+        &steps_of_jobs,
+        &collector.resources,
+        &collector.resource_types,
+        &mut diagnostics,
+    );
+    Ok((pipeline, diagnostics))
+}
+
+// Collects every problem of the pipeline rather than stopping at the first
+// one. Cook runs the same checks and fails if any problem is found.
+pub fn validate(pipeline: &Pipeline) -> Vec<Diagnostic> {
+    validate_with_options(pipeline, &CookOptions::default())
+}
+
+pub fn validate_with_options(pipeline: &Pipeline, options: &CookOptions) -> Vec<Diagnostic> {
+    // Serde errors are reported by cook.
+    diagnose(pipeline, options)
+        .map(|(_, diagnostics)| diagnostics)
+        .unwrap_or_default()
+}
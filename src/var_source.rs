@@ -0,0 +1,253 @@
+use crate::schema::Duration;
+use crate::schema::Identifier;
+use crate::schema::Var;
+use serde::ser::SerializeStruct;
+use serde::Serialize;
+use serde::Serializer;
+use std::collections::BTreeMap;
+
+// https://concourse-ci.org/vault-credential-manager.html
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct VaultConfig {
+    pub(crate) url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) ca_cert: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) path_prefix: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) lookup_templates: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) shared_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) namespace: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) client_cert: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) client_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) client_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) auth_backend: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) auth_params: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) auth_max_ttl: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) auth_retry_max: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) auth_retry_initial: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) login_timeout: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) query_timeout: Option<Duration>,
+}
+
+impl VaultConfig {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_ca_cert(mut self, ca_cert: &str) -> Self {
+        self.ca_cert = Some(ca_cert.to_string());
+        self
+    }
+
+    pub fn with_path_prefix(mut self, path_prefix: &str) -> Self {
+        self.path_prefix = Some(path_prefix.to_string());
+        self
+    }
+
+    pub fn with_lookup_templates(mut self, lookup_templates: &[&str]) -> Self {
+        self.lookup_templates = lookup_templates.iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    pub fn with_shared_path(mut self, shared_path: &str) -> Self {
+        self.shared_path = Some(shared_path.to_string());
+        self
+    }
+
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.namespace = Some(namespace.to_string());
+        self
+    }
+
+    pub fn with_client_cert(mut self, client_cert: &str, client_key: &str) -> Self {
+        self.client_cert = Some(client_cert.to_string());
+        self.client_key = Some(client_key.to_string());
+        self
+    }
+
+    pub fn with_client_token(mut self, client_token: &str) -> Self {
+        self.client_token = Some(client_token.to_string());
+        self
+    }
+
+    pub fn with_auth(mut self, auth_backend: &str, auth_params: &[(&str, &str)]) -> Self {
+        self.auth_backend = Some(auth_backend.to_string());
+        self.auth_params = auth_params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        self
+    }
+
+    pub fn with_auth_max_ttl(mut self, auth_max_ttl: Duration) -> Self {
+        self.auth_max_ttl = Some(auth_max_ttl);
+        self
+    }
+
+    pub fn with_auth_retry(mut self, initial: Duration, max: Duration) -> Self {
+        self.auth_retry_initial = Some(initial);
+        self.auth_retry_max = Some(max);
+        self
+    }
+
+    pub fn with_login_timeout(mut self, login_timeout: Duration) -> Self {
+        self.login_timeout = Some(login_timeout);
+        self
+    }
+
+    pub fn with_query_timeout(mut self, query_timeout: Duration) -> Self {
+        self.query_timeout = Some(query_timeout);
+        self
+    }
+}
+
+// https://concourse-ci.org/aws-ssm-credential-manager.html
+// https://concourse-ci.org/aws-asm-credential-manager.html
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AwsConfig {
+    pub(crate) region: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) access_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) secret_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) session_token: Option<String>,
+}
+
+impl AwsConfig {
+    pub fn new(region: &str) -> Self {
+        Self {
+            region: region.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_credentials(mut self, access_key: &str, secret_key: &str) -> Self {
+        self.access_key = Some(access_key.to_string());
+        self.secret_key = Some(secret_key.to_string());
+        self
+    }
+
+    pub fn with_session_token(mut self, session_token: &str) -> Self {
+        self.session_token = Some(session_token.to_string());
+        self
+    }
+}
+
+// https://concourse-ci.org/vars.html#dummy-var-source
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DummyConfig {
+    pub(crate) vars: BTreeMap<String, String>,
+}
+
+// https://concourse-ci.org/vars.html#var-sources
+#[derive(Debug, Clone, PartialEq)]
+pub enum VarSource {
+    Vault {
+        name: Identifier,
+        config: Box<VaultConfig>,
+    },
+    Ssm {
+        name: Identifier,
+        config: AwsConfig,
+    },
+    SecretsManager {
+        name: Identifier,
+        config: AwsConfig,
+    },
+    Dummy {
+        name: Identifier,
+        config: DummyConfig,
+    },
+}
+
+impl Serialize for VarSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("VarSource", 3)?;
+        state.serialize_field("name", &self.name())?;
+        state.serialize_field("type", self.type_name())?;
+        match self {
+            Self::Vault { ref config, .. } => state.serialize_field("config", config.as_ref())?,
+            Self::Ssm { ref config, .. } | Self::SecretsManager { ref config, .. } => {
+                state.serialize_field("config", config)?
+            }
+            Self::Dummy { ref config, .. } => state.serialize_field("config", config)?,
+        }
+        state.end()
+    }
+}
+
+impl VarSource {
+    pub fn vault(name: &str, config: VaultConfig) -> Self {
+        Self::Vault {
+            name: name.to_string(),
+            config: Box::new(config),
+        }
+    }
+
+    pub fn ssm(name: &str, config: AwsConfig) -> Self {
+        Self::Ssm {
+            name: name.to_string(),
+            config,
+        }
+    }
+
+    pub fn secrets_manager(name: &str, config: AwsConfig) -> Self {
+        Self::SecretsManager {
+            name: name.to_string(),
+            config,
+        }
+    }
+
+    pub fn dummy(name: &str, vars: &[(&str, &str)]) -> Self {
+        Self::Dummy {
+            name: name.to_string(),
+            config: DummyConfig {
+                vars: vars
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            },
+        }
+    }
+
+    pub fn name(&self) -> Identifier {
+        match self {
+            Self::Vault { ref name, .. }
+            | Self::Ssm { ref name, .. }
+            | Self::SecretsManager { ref name, .. }
+            | Self::Dummy { ref name, .. } => name.clone(),
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::Vault { .. } => "vault",
+            Self::Ssm { .. } => "ssm",
+            Self::SecretsManager { .. } => "secretsmanager",
+            Self::Dummy { .. } => "dummy",
+        }
+    }
+
+    // Returns the handle of a var in this source, which renders as
+    // '((name:path))'. Use Var::field() to refer to a field of the secret.
+    pub fn var(&self, path: &str) -> Var {
+        Var::from_parts(Some(self.name().as_str()), path, vec![])
+    }
+}